@@ -0,0 +1,25 @@
+use cgmath::Vector3 as Vec3;
+
+// sRGB 与线性空间的相互转换，渲染管线内部的颜色一律使用线性空间
+
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(c: f32) -> f32 {
+    let c = c.max(0.0);
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// 线性空间下的相对亮度 (Rec.709)
+pub fn luminance(c: Vec3<f32>) -> f32 {
+    c.x * 0.2126 + c.y * 0.7152 + c.z * 0.0722
+}
@@ -1,5 +1,6 @@
-use cgmath::{Vector3 as Vec3, Vector4 as Vec4};
+use cgmath::{Vector2 as Vec2, Vector3 as Vec3, Vector4 as Vec4};
 
+use crate::color::linear_to_srgb;
 use crate::{BLUE, FAR_PLANE, NEAR_PLANE};
 
 #[derive(Clone)]
@@ -8,6 +9,22 @@ pub struct FrameBuffer {
     pub height: usize,
     pub data: Vec<Vec4<f32>>,
     pub depth: Vec<f32>,
+    pub normal: Vec<Vec3<f32>>,      // 世界空间法线，背景处为零向量
+    pub ambient_occlusion: Vec<f32>, // 环境光遮蔽，1.0 表示无遮蔽；clear 时保留
+    pub velocity: Vec<Vec2<f32>>,    // 屏幕空间运动矢量（像素，x 向右 y 向下），背景处为零
+    pub object_id: Vec<u32>,         // 绘制该像素的物体编号（模型中每种材质或子网格各占一个），背景处为 BACKGROUND_ID
+}
+
+// 背景像素的物体编号
+pub const BACKGROUND_ID: u32 = 0;
+
+// 随颜色一起写入的几何信息
+#[derive(Clone, Copy)]
+pub struct PixelGeometry {
+    pub depth: f32,
+    pub normal: Vec3<f32>,
+    pub velocity: Vec2<f32>,
+    pub object_id: u32,
 }
 
 impl FrameBuffer {
@@ -17,22 +34,57 @@ impl FrameBuffer {
             height,
             data: vec![Vec4::new(0., 0., 0., 0.); width * height],
             depth: vec![1.0; width * height],
+            normal: vec![Vec3::new(0., 0., 0.); width * height],
+            ambient_occlusion: vec![1.0; width * height],
+            velocity: vec![Vec2::new(0., 0.); width * height],
+            object_id: vec![BACKGROUND_ID; width * height],
         }
     }
 
     pub fn clear(&mut self, color: Vec4<f32>) {
         self.data.fill(color);
         self.depth.fill(1.0);
+        self.normal.fill(Vec3::new(0., 0., 0.));
+        self.velocity.fill(Vec2::new(0., 0.));
+        self.object_id.fill(BACKGROUND_ID);
     }
 
-    pub fn put_pixel(&mut self, x: usize, y: usize, color: Vec4<f32>, depth: f32) {
-        if x < self.width && y < self.height {
+    pub fn put_pixel(&mut self, x: usize, y: usize, color: Vec4<f32>, geometry: PixelGeometry) {
+        if self.depth_test(x, y, geometry.depth) {
             let idx = y * self.width + x;
-            // 确保深度值在 [0, 1] 范围内，值越小越近
-            if depth >= 0.0 && depth <= 1.0 && depth < self.depth[idx] {
-                self.data[idx] = color;
-                self.depth[idx] = depth;
-            }
+            self.data[idx] = color;
+            self.write_geometry(idx, geometry);
+        }
+    }
+
+    // 只写入几何信息不写颜色，用于 SSAO 预渲染与延迟渲染的几何阶段
+    pub fn put_depth(&mut self, x: usize, y: usize, geometry: PixelGeometry) {
+        if self.depth_test(x, y, geometry.depth) {
+            self.write_geometry(y * self.width + x, geometry);
+        }
+    }
+
+    fn write_geometry(&mut self, idx: usize, geometry: PixelGeometry) {
+        self.depth[idx] = geometry.depth;
+        self.normal[idx] = geometry.normal;
+        self.velocity[idx] = geometry.velocity;
+        self.object_id[idx] = geometry.object_id;
+    }
+
+    pub fn depth_test(&self, x: usize, y: usize, depth: f32) -> bool {
+        // 确保深度值在 [0, 1] 范围内，值越小越近
+        x < self.width
+            && y < self.height
+            && depth >= 0.0
+            && depth <= 1.0
+            && depth < self.depth[y * self.width + x]
+    }
+
+    pub fn ambient_occlusion_at(&self, x: usize, y: usize) -> f32 {
+        if x < self.width && y < self.height {
+            self.ambient_occlusion[y * self.width + x]
+        } else {
+            1.0
         }
     }
 
@@ -86,6 +138,7 @@ impl FrameBuffer {
         (f.clamp(0.0, 1.0) * 255.0 + 0.5).floor() as u8
     }
 
+    // 缓冲中存的是线性颜色，保存时编码为 sRGB
     pub fn save_as_image(&self, filepath: &str) -> Result<(), image::ImageError> {
         use image::{ImageBuffer, Rgba};
         let mut img = ImageBuffer::new(self.width as u32, self.height as u32);
@@ -96,9 +149,9 @@ impl FrameBuffer {
                 let color = self.data[idx];
 
                 let a = Self::float_to_u8(color.w);
-                let r = Self::float_to_u8(color.x);
-                let g = Self::float_to_u8(color.y);
-                let b = Self::float_to_u8(color.z);
+                let r = Self::float_to_u8(linear_to_srgb(color.x));
+                let g = Self::float_to_u8(linear_to_srgb(color.y));
+                let b = Self::float_to_u8(linear_to_srgb(color.z));
 
                 img.put_pixel(x as u32, y as u32, Rgba([r, g, b, a]));
             }
@@ -107,6 +160,34 @@ impl FrameBuffer {
         img.save(filepath)
     }
 
+    // 把运动矢量保存为 OpenEXR，R/G 通道为降采样后分辨率下的 x/y 位移（像素），供合成软件使用
+    pub fn save_velocity_as_image(
+        &self,
+        filepath: &str,
+        factor: usize,
+    ) -> Result<(), image::ImageError> {
+        use image::{ImageBuffer, Rgb};
+        let factor = factor.max(1);
+        let new_width = self.width / factor;
+        let new_height = self.height / factor;
+        let mut img = ImageBuffer::new(new_width as u32, new_height as u32);
+
+        for y in 0..new_height {
+            for x in 0..new_width {
+                let mut sum = Vec2::new(0., 0.);
+                for fy in 0..factor {
+                    for fx in 0..factor {
+                        sum += self.velocity[(y * factor + fy) * self.width + x * factor + fx];
+                    }
+                }
+                let v = sum / (factor * factor * factor) as f32;
+                img.put_pixel(x as u32, y as u32, Rgb([v.x, v.y, 0.0f32]));
+            }
+        }
+
+        img.save(filepath)
+    }
+
     // 将深度缓冲可视化为图片（近→亮，远→暗）
     pub fn save_depth_as_image(&self, filepath: &str) -> Result<(), image::ImageError> {
         use image::{ImageBuffer, Rgba};
@@ -1,16 +1,102 @@
 use serde::Deserialize;
 
+use crate::BLACK;
+
 #[derive(Debug, Deserialize)]
 pub struct JsonConfig {
     pub models: Vec<ModelConfig>,
     pub camera: CameraConfig,
     pub light: LightConfig,
+    #[serde(default)]
+    pub pipeline: PipelineConfig,
+    #[serde(default)]
+    pub animation: Option<AnimationConfig>,
+    #[serde(default)]
+    pub ssao: Option<SsaoConfig>,
+    #[serde(default)]
+    pub motion_blur: Option<MotionBlurConfig>,
+    #[serde(default)]
+    pub bloom: Option<BloomConfig>,
+    #[serde(default)]
+    pub tone_mapping: ToneMappingConfig,
+    #[serde(default)]
+    pub grading: Option<GradingConfig>,
+    #[serde(default)]
+    pub glitch: GlitchConfig,
+    #[serde(default)]
+    pub hatching: HatchingConfig,
+    #[serde(default)]
+    pub ink: InkConfig,
+    #[serde(default)]
+    pub kuwahara: KuwaharaConfig,
+    #[serde(default)]
+    pub watercolor: WatercolorConfig,
+    #[serde(default)]
+    pub halftone: HalftoneConfig,
+    #[serde(default)]
+    pub dither: DitherConfig,
+    #[serde(default)]
+    pub pixel_art: PixelArtConfig,
+    #[serde(default)]
+    pub cel: CelConfig,
+    #[serde(default)]
+    pub gooch: GoochConfig,
+    #[serde(default)]
+    pub hair: HairConfig,
+    #[serde(default)]
+    pub post_process: Option<Vec<PostProcessConfig>>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CameraConfig {
     pub position: [f32; 3],
     pub angle: [f32; 3],
+    #[serde(default)]
+    pub depth_of_field: Option<DepthOfFieldConfig>,
+}
+
+// 渲染管线配置
+// render_path："forward"（光栅化时逐片元着色）/ "deferred"（先写 G-buffer，再对每个可见像素着色一次）
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct PipelineConfig {
+    pub render_path: String,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            render_path: "forward".to_string(),
+        }
+    }
+}
+
+// 景深配置，不写则不开启
+// focus_model 为对焦模型在 models 中的序号（从 0 开始），设置后忽略 focus_distance
+// unit_scale 为一个世界单位对应的米数，sensor_height 为底片高度 (mm)，焦距由视场角换算
+// max_blur 为 1 倍 SSAA 下弥散圆半径的上限（像素）
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct DepthOfFieldConfig {
+    pub f_stop: f32,
+    pub focus_distance: f32,
+    pub focus_model: Option<usize>,
+    pub unit_scale: f32,
+    pub sensor_height: f32,
+    pub max_blur: f32,
+}
+
+impl Default for DepthOfFieldConfig {
+    fn default() -> Self {
+        Self {
+            f_stop: 2.8,
+            focus_distance: 10.0,
+            focus_model: None,
+            unit_scale: 0.1,
+            sensor_height: 24.0,
+            max_blur: 16.0,
+        }
+    }
 }
 #[derive(Debug, Deserialize)]
 pub struct ModelConfig {
@@ -20,6 +106,104 @@ pub struct ModelConfig {
     pub position: [f32; 3],
     pub angle: [f32; 3],
     pub scale: f32,
+    #[serde(default)]
+    pub sampler: SamplerConfig,
+    // 旧版配置直接写在模型上的过滤方式与各向异性上限，现已移入 sampler，填写时仍会生效并给出提示
+    #[serde(default)]
+    pub filter: Option<String>,
+    #[serde(default)]
+    pub max_anisotropy: Option<u32>,
+    #[serde(default)]
+    pub parallax: Option<ParallaxConfig>,
+    #[serde(default)]
+    pub spin: [f32; 3], // 动画中绕自身原点的旋转角速度（度/秒）
+    #[serde(default)]
+    pub outline: Option<HullOutlineConfig>,
+    #[serde(default)]
+    pub matcap_path: String, // matcap 贴图路径，着色器方法为 "matcap" 时使用，留空则只显示基础颜色
+    #[serde(default)]
+    pub submeshes: Vec<SubmeshConfig>,
+}
+
+// 为模型的一部分单独指定着色器（如头发用 "hair"，其余部分仍用命令行给出的着色器方法）
+// name 与 OBJ 中的物体名（o）、组名（g）或材质名（usemtl）之一相同即匹配，按顺序取第一条匹配的配置
+// shader 留空则使用命令行给出的着色器方法（只替换材质时使用）；material 留空则沿用模型的 material
+#[derive(Debug, Deserialize)]
+pub struct SubmeshConfig {
+    pub name: String,
+    #[serde(default)]
+    pub shader: String,
+    #[serde(default)]
+    pub material: String,
+}
+
+// 反转外壳描边配置，不写则不描边
+// units："world"（width 为世界单位）/ "screen"（width 为 1 倍 SSAA 下的像素数，远近粗细一致）
+// vertex_color_width 为 true 时宽度再乘以顶点颜色的亮度
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct HullOutlineConfig {
+    pub width: f32,
+    pub units: String,
+    pub color: [f32; 3],
+    pub vertex_color_width: bool,
+}
+
+impl Default for HullOutlineConfig {
+    fn default() -> Self {
+        Self {
+            width: 2.0,
+            units: "screen".to_string(),
+            color: BLACK.truncate().into(),
+            vertex_color_width: false,
+        }
+    }
+}
+
+// 纹理采样器配置
+// wrap_u / wrap_v："repeat" / "mirrored_repeat" / "clamp_to_edge" / "clamp_to_border"
+// filter："nearest" / "bilinear" / "trilinear" / "anisotropic"
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SamplerConfig {
+    pub wrap_u: String,
+    pub wrap_v: String,
+    pub border_color: [f32; 3],
+    pub filter: String,
+    pub max_anisotropy: u32,
+}
+
+impl Default for SamplerConfig {
+    fn default() -> Self {
+        Self {
+            wrap_u: "repeat".to_string(),
+            wrap_v: "repeat".to_string(),
+            border_color: [0.0, 0.0, 0.0],
+            filter: "nearest".to_string(),
+            max_anisotropy: crate::texture::MAX_ANISOTROPY,
+        }
+    }
+}
+
+// 视差遮蔽贴图配置，height_path 为高度图路径（白色为凸起）
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ParallaxConfig {
+    pub height_path: String,
+    pub steps: u32,
+    pub depth_scale: f32,
+    pub self_shadow: bool,
+}
+
+impl Default for ParallaxConfig {
+    fn default() -> Self {
+        Self {
+            height_path: String::new(),
+            steps: 32,
+            depth_scale: 0.05,
+            self_shadow: false,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -27,3 +211,626 @@ pub struct LightConfig {
     pub direction: [f32; 3],
     pub color: [f32; 3],
 }
+
+// 动画配置，不写则只渲染一帧并保存为 output1.png
+// 每帧保存为 output_dir/frame_0000.png，开启 save_velocity 时同时保存 velocity_0000.exr
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct AnimationConfig {
+    pub frames: usize,
+    pub fps: f32,
+    pub output_dir: String,
+    pub save_velocity: bool,
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        Self {
+            frames: 120,
+            fps: 30.0,
+            output_dir: "output".to_string(),
+            save_velocity: false,
+        }
+    }
+}
+
+// 运动模糊配置，不写则不开启
+// shutter_angle 为快门角度（度），max_blur 为 1 倍 SSAA 下模糊长度的上限（像素）
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct MotionBlurConfig {
+    pub shutter_angle: f32,
+    pub samples: usize,
+    pub max_blur: f32,
+}
+
+impl Default for MotionBlurConfig {
+    fn default() -> Self {
+        Self {
+            shutter_angle: 180.0,
+            samples: 16,
+            max_blur: 32.0,
+        }
+    }
+}
+
+// 屏幕空间环境光遮蔽配置，不写则不开启
+// radius 为世界单位下的采样半径，blur_radius 为双边模糊的像素半径
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SsaoConfig {
+    pub radius: f32,
+    pub samples: usize,
+    pub bias: f32,
+    pub intensity: f32,
+    pub blur_radius: usize,
+}
+
+impl Default for SsaoConfig {
+    fn default() -> Self {
+        Self {
+            radius: 1.5,
+            samples: 16,
+            bias: 0.05,
+            intensity: 1.5,
+            blur_radius: 4,
+        }
+    }
+}
+
+// 泛光配置，不写则不开启
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct BloomConfig {
+    pub threshold: f32,
+    pub knee: f32,
+    pub intensity: f32,
+    pub radius: f32,
+    pub levels: usize,
+}
+
+impl Default for BloomConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 1.0,
+            knee: 0.5,
+            intensity: 0.6,
+            radius: 1.0,
+            levels: 6,
+        }
+    }
+}
+
+// 色调映射配置
+// operator："clamp" / "reinhard" / "aces" / "agx"
+// exposure 为曝光 (EV)，开启 auto_exposure 时作为曝光补偿使用
+// histogram_low / histogram_high 为自动曝光统计时保留的亮度百分位区间
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ToneMappingConfig {
+    pub operator: String,
+    pub exposure: f32,
+    pub auto_exposure: bool,
+    pub histogram_low: f32,
+    pub histogram_high: f32,
+}
+
+impl Default for ToneMappingConfig {
+    fn default() -> Self {
+        Self {
+            operator: "clamp".to_string(),
+            exposure: 0.0,
+            auto_exposure: false,
+            histogram_low: 0.5,
+            histogram_high: 0.95,
+        }
+    }
+}
+
+// 输出前的调色配置，不写则不调色
+// lut_path 为 .cube 格式的 3D LUT 路径（留空则不使用），按 sRGB 编码后的颜色查表
+// lut_interpolation："trilinear" / "tetrahedral"
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct GradingConfig {
+    pub lift: [f32; 3],
+    pub gamma: [f32; 3],
+    pub gain: [f32; 3],
+    pub saturation: f32,
+    pub lut_path: String,
+    pub lut_interpolation: String,
+}
+
+impl Default for GradingConfig {
+    fn default() -> Self {
+        Self {
+            lift: [0.0, 0.0, 0.0],
+            gamma: [1.0, 1.0, 1.0],
+            gain: [1.0, 1.0, 1.0],
+            saturation: 1.0,
+            lut_path: String::new(),
+            lut_interpolation: "tetrahedral".to_string(),
+        }
+    }
+}
+
+// 故障效果配置，仅在着色器方法为 "glitch" 时使用
+// 像素单位的参数均按 1920x1080 输出填写，开启 SSAA 时会自动放大
+// hold_frames 为动画中切片与损坏块保持不变的帧数
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct GlitchConfig {
+    pub seed: u32,
+    pub intensity: f32,
+    pub rgb_shift: f32,
+    pub slice_count: usize,
+    pub slice_shift: f32,
+    pub block_size: usize,
+    pub block_chance: f32,
+    pub scanline: f32,
+    pub scanline_period: f32,
+    pub noise: f32,
+    pub hold_frames: usize,
+}
+
+impl Default for GlitchConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            intensity: 1.0,
+            rgb_shift: 6.0,
+            slice_count: 12,
+            slice_shift: 60.0,
+            block_size: 32,
+            block_chance: 0.03,
+            scanline: 0.25,
+            scanline_period: 3.0,
+            noise: 0.08,
+            hold_frames: 3,
+        }
+    }
+}
+
+// 水墨风格配置，仅在着色器方法为 "ink" 时使用
+// 随机墨点由 seed 和模型空间坐标决定，改变 seed 得到另一组墨点；grain_size 为墨点格子的模型空间边长
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct InkConfig {
+    pub seed: u32,
+    pub grain_size: f32,
+}
+
+impl Default for InkConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            grain_size: 0.02,
+        }
+    }
+}
+
+// Gooch 技术插图着色配置，仅在着色器方法为 "gooch" 时使用
+// cool / warm 为背光面与受光面的基础色（线性空间），alpha / beta 为两端混入表面颜色的比例
+// highlight 为白色高光的强度，0 为不加高光
+// edges 为配套的线稿描边：depth_threshold 控制轮廓线，normal_angle 控制折痕线，参数与 post_process 中的 "edges" 相同
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct GoochConfig {
+    pub cool: [f32; 3],
+    pub warm: [f32; 3],
+    pub alpha: f32,
+    pub beta: f32,
+    pub highlight: f32,
+    pub edges: EdgeOutlineConfig,
+}
+
+impl Default for GoochConfig {
+    fn default() -> Self {
+        Self {
+            cool: [0.0, 0.0, 0.55],
+            warm: [0.3, 0.3, 0.0],
+            alpha: 0.25,
+            beta: 0.5,
+            highlight: 1.0,
+            edges: EdgeOutlineConfig::default(),
+        }
+    }
+}
+
+// 可配置卡通渲染的配置，仅在着色器方法为 "cel" 时使用
+// ramp_path 为色阶贴图路径：横向按光照项取值，纵向按表面朝向相机的程度取值（高度为 1 即一维色阶），黑色为阴影色、白色为受光色，
+// 像素值不做 sRGB 解码，直接与 bands 的 color 一样作为混合权重；
+// 留空则使用 bands，光照项不低于 threshold 时取该条的 color 作为混合权重，低于所有阈值的部分为纯阴影色
+// specular_shape："none" / "soft" / "hard"，hard 时由 specular_size 与 specular_softness 决定高光色块的大小与边缘
+// rim_strength 为 0 时不加边缘光；rim_softness 为 0 时边缘光是一条硬边
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct CelConfig {
+    pub ramp_path: String,
+    pub bands: Vec<CelBandConfig>,
+    pub half_lambert: bool,
+    pub shadow_color: [f32; 3],
+    pub rim_color: [f32; 3],
+    pub rim_strength: f32,
+    pub rim_power: f32,
+    pub rim_threshold: f32,
+    pub rim_softness: f32,
+    pub specular_shape: String,
+    pub specular_size: f32,
+    pub specular_softness: f32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CelBandConfig {
+    pub threshold: f32,
+    pub color: [f32; 3],
+}
+
+impl Default for CelConfig {
+    fn default() -> Self {
+        Self {
+            ramp_path: String::new(),
+            bands: vec![
+                CelBandConfig {
+                    threshold: 0.2,
+                    color: [0.6, 0.6, 0.6],
+                },
+                CelBandConfig {
+                    threshold: 0.6,
+                    color: [1.0, 1.0, 1.0],
+                },
+            ],
+            half_lambert: false,
+            shadow_color: [0.25, 0.2, 0.35],
+            rim_color: [1.0, 1.0, 1.0],
+            rim_strength: 0.4,
+            rim_power: 2.0,
+            rim_threshold: 0.5,
+            rim_softness: 0.05,
+            specular_shape: "hard".to_string(),
+            specular_size: 0.3,
+            specular_softness: 0.1,
+        }
+    }
+}
+
+// 头发着色器的配置，模型或子网格的着色器为 "hair" 时使用
+// direction："u" / "v"（沿纹理坐标方向）/ "flow_map"（由 flow_map_path 的 RG 通道给出切线空间走向）/ "world"（固定为 world_direction）；
+// 没有 UV 的模型一律使用 world_direction
+// shift_map_path / noise_map_path 为按 UV 采样的偏移贴图与次高光遮罩，留空则由 seed 与 strand_density 程序生成沿发丝的条纹噪声
+// primary / secondary 为两层高光，shift 为切线沿法线的偏移（正值移向发梢），exponent 越大高光带越窄
+// toon 为 true 时漫反射使用与 "toon" 着色器相同的色阶，高光变为硬边色带；toon_threshold / toon_softness 只控制高光色带的阈值与边缘
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct HairConfig {
+    pub direction: String,
+    pub flow_map_path: String,
+    pub world_direction: [f32; 3],
+    pub shift_map_path: String,
+    pub shift_strength: f32,
+    pub noise_map_path: String,
+    pub strand_density: f32,
+    pub seed: u32,
+    pub primary: HairLobeConfig,
+    pub secondary: HairLobeConfig,
+    pub toon: bool,
+    pub toon_threshold: f32,
+    pub toon_softness: f32,
+}
+
+// 一层高光的配置，只写部分字段时其余字段取白色、shift 0、exponent 40、strength 0.5
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct HairLobeConfig {
+    pub color: [f32; 3],
+    pub shift: f32,
+    pub exponent: f32,
+    pub strength: f32,
+}
+
+impl Default for HairLobeConfig {
+    fn default() -> Self {
+        Self {
+            color: [1.0, 1.0, 1.0],
+            shift: 0.0,
+            exponent: 40.0,
+            strength: 0.5,
+        }
+    }
+}
+
+impl Default for HairConfig {
+    fn default() -> Self {
+        Self {
+            direction: "v".to_string(),
+            flow_map_path: String::new(),
+            world_direction: [0.0, 1.0, 0.0],
+            shift_map_path: String::new(),
+            shift_strength: 0.3,
+            noise_map_path: String::new(),
+            strand_density: 40.0,
+            seed: 0,
+            primary: HairLobeConfig {
+                color: [1.0, 1.0, 1.0],
+                shift: 0.1,
+                exponent: 80.0,
+                strength: 0.6,
+            },
+            secondary: HairLobeConfig {
+                color: [1.0, 1.0, 1.0],
+                shift: -0.1,
+                exponent: 20.0,
+                strength: 0.4,
+            },
+            toon: false,
+            toon_threshold: 0.5,
+            toon_softness: 0.02,
+        }
+    }
+}
+
+// 素描排线配置，仅在着色器方法为 "hatching" 时使用
+// space："screen"（笔触固定在屏幕上）/ "uv"（笔触贴在模型 UV 上，没有 UV 的模型退回屏幕空间）
+// scale：screen 时为一张色调图在 1 倍 SSAA 下覆盖的像素数，uv 时为 UV 方向的重复次数
+// tam_paths 为由浅到深排列的色调图路径，留空则程序生成 levels 级色调图
+// cross_threshold 为开始叠加交叉排线的色调（0 ~ 1），paper_path 留空则程序生成纸纹，paper_strength 为 0 时不叠加纸纹
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct HatchingConfig {
+    pub space: String,
+    pub scale: f32,
+    pub levels: usize,
+    pub seed: u32,
+    pub tam_paths: Vec<String>,
+    pub cross_hatch: bool,
+    pub cross_threshold: f32,
+    pub paper_path: String,
+    pub paper_strength: f32,
+    pub paper_scale: f32,
+}
+
+impl Default for HatchingConfig {
+    fn default() -> Self {
+        Self {
+            space: "screen".to_string(),
+            scale: 256.0,
+            levels: 6,
+            seed: 0,
+            tam_paths: Vec::new(),
+            cross_hatch: true,
+            cross_threshold: 0.35,
+            paper_path: String::new(),
+            paper_strength: 0.5,
+            paper_scale: 512.0,
+        }
+    }
+}
+
+// 各向异性 Kuwahara 滤波配置，着色器方法为 "kuwahara" 时使用
+// radius 为 1 倍 SSAA 下的滤波半径（像素），sharpness 越大色块边缘越锐利，alpha 越小笔触越细长
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct KuwaharaConfig {
+    pub radius: f32,
+    pub sharpness: f32,
+    pub alpha: f32,
+    pub tensor_sigma: f32,
+}
+
+impl Default for KuwaharaConfig {
+    fn default() -> Self {
+        Self {
+            radius: 4.0,
+            sharpness: 8.0,
+            alpha: 1.0,
+            tensor_sigma: 2.0,
+        }
+    }
+}
+
+// 水彩风格化配置，着色器方法为 "watercolor" 时使用
+// bleed_radius 与 paper_scale 为 1 倍 SSAA 下的像素数；bleed_depth 为线性深度的相对差，超过即视为不同表面
+// paper_path 为纸纹图片路径（按高度图读取，不做 sRGB 解码，中灰为平整），留空则使用程序生成的纸纹
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct WatercolorConfig {
+    pub seed: u32,
+    pub bleed_radius: f32,
+    pub bleed_depth: f32,
+    pub edge_darkening: f32,
+    pub granulation: f32,
+    pub turbulence: f32,
+    pub paper_path: String,
+    pub paper_scale: f32,
+}
+
+impl Default for WatercolorConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            bleed_radius: 6.0,
+            bleed_depth: 0.05,
+            edge_darkening: 3.0,
+            granulation: 0.6,
+            turbulence: 0.4,
+            paper_path: String::new(),
+            paper_scale: 256.0,
+        }
+    }
+}
+
+// CMYK 半调网点配置，着色器方法为 "halftone" 时使用
+// cell_size 为 1 倍 SSAA 下的网格间距（像素），angles 为 C / M / Y / K 的网角（度）
+// inks 为 C / M / Y / K 四种油墨的颜色，paper 为纸张颜色，均按 sRGB 填写（与图片编辑软件中的数值一致）
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct HalftoneConfig {
+    pub cell_size: f32,
+    pub angles: [f32; 4],
+    pub inks: [[f32; 3]; 4],
+    pub paper: [f32; 3],
+}
+
+impl Default for HalftoneConfig {
+    fn default() -> Self {
+        Self {
+            cell_size: 8.0,
+            angles: [15.0, 75.0, 0.0, 45.0],
+            inks: [
+                [0.0, 1.0, 1.0],
+                [1.0, 0.0, 1.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0],
+            ],
+            paper: [1.0, 1.0, 1.0],
+        }
+    }
+}
+
+// 抖动配置，着色器方法为 "dither" 时使用
+// method："bayer"（有序抖动）/ "floyd_steinberg"（误差扩散）
+// matrix_size 为 Bayer 矩阵边长（2 / 4 / 8 ...），spread 为有序抖动的阈值扰动幅度，通常取调色板相邻颜色的间距
+// pixel_size 为 1 倍 SSAA 下一个抖动像素的边长，palette 为调色板，按 sRGB 填写
+// palette_path 为调色板文件（.hex / .gpl 或图片），填写时代替 palette
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct DitherConfig {
+    pub method: String,
+    pub matrix_size: usize,
+    pub spread: f32,
+    pub pixel_size: usize,
+    pub palette: Vec<[f32; 3]>,
+    pub palette_path: String,
+}
+
+impl Default for DitherConfig {
+    fn default() -> Self {
+        Self {
+            method: "bayer".to_string(),
+            matrix_size: 4,
+            spread: 1.0,
+            pixel_size: 1,
+            palette: vec![[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]],
+            palette_path: String::new(),
+        }
+    }
+}
+
+// 像素画配置，着色器方法为 "pixel_art" 时使用
+// pixel_size 为 1 倍 SSAA 下一个像素画像素的边长
+// downsample："nearest"（取块中心像素，等同于低分辨率渲染）/ "average"（块内平均）
+// palette_path 为调色板文件（.hex / .gpl 或图片），不填时使用 palette（按 sRGB 填写）；
+// 两者都为空时按 quantizer 从画面生成 colors 种颜色
+// quantizer："median_cut"（中位切分）/ "kmeans"（以中位切分为初值迭代 iterations 次）
+// dither 为有序抖动的幅度（sRGB 单位），0 为不抖动
+// outline 为 true 时在物体轮廓内侧画一圈 outline_color（sRGB），
+// outline_depth 为同一物体内被视为轮廓的线性深度相对差
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct PixelArtConfig {
+    pub pixel_size: usize,
+    pub downsample: String,
+    pub palette_path: String,
+    pub palette: Vec<[f32; 3]>,
+    pub quantizer: String,
+    pub colors: usize,
+    pub iterations: usize,
+    pub dither: f32,
+    pub outline: bool,
+    pub outline_color: [f32; 3],
+    pub outline_depth: f32,
+}
+
+impl Default for PixelArtConfig {
+    fn default() -> Self {
+        Self {
+            pixel_size: 4,
+            downsample: "nearest".to_string(),
+            palette_path: String::new(),
+            palette: Vec::new(),
+            quantizer: "kmeans".to_string(),
+            colors: 16,
+            iterations: 8,
+            dither: 0.0,
+            outline: true,
+            outline_color: BLACK.truncate().into(),
+            outline_depth: 0.2,
+        }
+    }
+}
+
+// 后处理链中的一个 pass，由 "pass" 字段指定种类，其余字段为该 pass 的参数
+// pass："depth_of_field" / "motion_blur" / "bloom" / "outline" / "edges" / "tone_mapping" / "grading" / "glitch" /
+//       "kuwahara" / "watercolor" / "halftone" / "dither" / "pixel_art"
+// 写了 post_process 时按数组顺序执行，并忽略外层的 bloom、tone_mapping 等配置与着色器方法自带的描边；
+// 不写时按旧的固定顺序由外层配置生成
+#[derive(Debug, Deserialize)]
+#[serde(tag = "pass", rename_all = "snake_case")]
+pub enum PostProcessConfig {
+    DepthOfField(DepthOfFieldConfig),
+    MotionBlur(MotionBlurConfig),
+    Bloom(BloomConfig),
+    Outline(OutlineConfig),
+    Edges(EdgeOutlineConfig),
+    ToneMapping(ToneMappingConfig),
+    Grading(GradingConfig),
+    Glitch(GlitchConfig),
+    Kuwahara(KuwaharaConfig),
+    Watercolor(WatercolorConfig),
+    Halftone(HalftoneConfig),
+    Dither(DitherConfig),
+    PixelArt(PixelArtConfig),
+}
+
+// 描边配置
+// source："color" / "depth"
+// line_width 为 1 倍 SSAA 下的线宽（像素）
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct OutlineConfig {
+    pub source: String,
+    pub threshold: f32,
+    pub line_width: usize,
+    pub color: [f32; 3],
+}
+
+impl Default for OutlineConfig {
+    fn default() -> Self {
+        Self {
+            source: "depth".to_string(),
+            threshold: 0.1,
+            line_width: 1,
+            color: BLACK.truncate().into(),
+        }
+    }
+}
+
+// 线稿描边配置：深度、法线、物体编号三种边缘各自设置阈值与颜色
+// depth_threshold 为相邻像素线性深度的相对差，normal_angle 为相邻像素法线夹角（度），小于等于 0 时关闭该项
+// object_id 为 true 时描出不同物体之间、以及同一模型不同材质或子网格之间的分界
+// line_width 为 1 倍 SSAA 下的线宽（像素）
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct EdgeOutlineConfig {
+    pub line_width: usize,
+    pub depth_threshold: f32,
+    pub depth_color: [f32; 3],
+    pub normal_angle: f32,
+    pub normal_color: [f32; 3],
+    pub object_id: bool,
+    pub object_id_color: [f32; 3],
+}
+
+impl Default for EdgeOutlineConfig {
+    fn default() -> Self {
+        Self {
+            line_width: 1,
+            depth_threshold: 0.05,
+            depth_color: BLACK.truncate().into(),
+            normal_angle: 40.0,
+            normal_color: BLACK.truncate().into(),
+            object_id: true,
+            object_id_color: BLACK.truncate().into(),
+        }
+    }
+}
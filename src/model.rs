@@ -1,13 +1,28 @@
 use crate::vertex::{ColoredVertex, Material, Triangle};
 use cgmath::{InnerSpace, Matrix4 as Mat4, SquareMatrix, Vector3 as Vec3, Vector2 as Vec2, Zero};
-use obj::Obj;
+use obj::{Obj, ObjMaterial};
 use std::path::Path;
 
-pub fn load_obj(path: &Path, material: &Material) -> Result<Vec<Triangle>, Box<dyn std::error::Error>> {
+// OBJ 中的一组三角形，记录所属的物体名（o）、组名（g）与材质名（usemtl），用于按子网格指定着色器
+pub struct MeshGroup {
+    pub object: String,
+    pub group: String,
+    pub material: Option<String>,
+    pub triangles: Vec<Triangle>,
+}
+
+impl MeshGroup {
+    pub fn matches(&self, name: &str) -> bool {
+        self.object == name || self.group == name || self.material.as_deref() == Some(name)
+    }
+}
+
+pub fn load_obj(path: &Path, material: &Material) -> Result<Vec<MeshGroup>, Box<dyn std::error::Error>> {
     let obj = Obj::load(Path::new(path)).expect("无法加载OBJ文件");
-    let mut triangles = Vec::new();
+    let mut groups = Vec::new();
     for object in obj.data.objects {
         for group in object.groups {
+            let mut triangles = Vec::new();
             for poly in group.polys {
                 if poly.0.len() == 3 {
                     let mut vertices = [ColoredVertex::default(); 3];
@@ -31,7 +46,7 @@ pub fn load_obj(path: &Path, material: &Material) -> Result<Vec<Triangle>, Box<d
                         };
                         vertices[i] = ColoredVertex {
                             pos: Vec3::new(pos[0] as f32, pos[1] as f32, pos[2] as f32),
-                            color: Vec3::new(0.8, 0.8, 0.8), // 默认灰色
+                            color: Vec3::new(0.604, 0.604, 0.604), // 默认灰色（线性空间，对应 sRGB 0.8）
                             normal,
                             uv,
                         };
@@ -39,8 +54,17 @@ pub fn load_obj(path: &Path, material: &Material) -> Result<Vec<Triangle>, Box<d
                     triangles.push(Triangle::new(vertices[0], vertices[1], vertices[2], material));
                 }
             }
+            groups.push(MeshGroup {
+                object: object.name.clone(),
+                group: group.name,
+                material: group.material.map(|m| match m {
+                    ObjMaterial::Ref(name) => name,
+                    ObjMaterial::Mtl(mtl) => mtl.name.clone(),
+                }),
+                triangles,
+            });
         }
     }
-    Ok(triangles)
+    Ok(groups)
 }
 
@@ -0,0 +1,63 @@
+// 确定性的随机数：全部由整数哈希得到，同一场景、同一种子每次渲染的结果完全一致
+use cgmath::Vector3 as Vec3;
+
+// 整数哈希到 [0, 1)
+pub fn hash(n: u32) -> f32 {
+    let mut x = n.wrapping_mul(0x9E37_79B9) ^ 0x85EB_CA6B;
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7FEB_352D);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846C_A68B);
+    x ^= x >> 16;
+    (x >> 8) as f32 / (1u32 << 24) as f32
+}
+
+pub fn hash_u32(n: u32) -> u32 {
+    let mut x = n ^ (n >> 16);
+    x = x.wrapping_mul(0x7FEB_352D);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846C_A68B);
+    x ^ (x >> 16)
+}
+
+// 由种子和两个整数得到 [0, 1) 的随机数
+pub fn random(seed: u32, a: u32, b: u32) -> f32 {
+    hash(seed ^ hash_u32(a.wrapping_mul(0x27D4_EB2D) ^ hash_u32(b)))
+}
+
+// 把空间位置量化到边长为 cell_size 的格子，同一格子内得到同一个 [0, 1) 随机数。
+// 随机数附着在位置上而不是每次调用都变化，传入模型空间位置时随机数随物体一起运动，动画各帧保持一致
+pub fn random_at(seed: u32, position: Vec3<f32>, cell_size: f32) -> f32 {
+    let cell = position / cell_size.max(1e-6);
+    let (x, y, z) = (
+        cell.x.floor() as i32 as u32,
+        cell.y.floor() as i32 as u32,
+        cell.z.floor() as i32 as u32,
+    );
+    random(seed ^ hash_u32(z.wrapping_mul(0x1656_67B1)), x, y)
+}
+
+// 二维值噪声：整数格点上取随机值并平滑插值，结果在 [0, 1) 之间
+pub fn value_noise(seed: u32, x: f32, y: f32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (tx, ty) = (smooth(x - x0), smooth(y - y0));
+    let (ix, iy) = (x0 as i32 as u32, y0 as i32 as u32);
+    let lattice = |dx: u32, dy: u32| random(seed, ix.wrapping_add(dx), iy.wrapping_add(dy));
+    let top = lattice(0, 0) + (lattice(1, 0) - lattice(0, 0)) * tx;
+    let bottom = lattice(0, 1) + (lattice(1, 1) - lattice(0, 1)) * tx;
+    top + (bottom - top) * ty
+}
+
+// 分形噪声：octaves 层频率逐层加倍、振幅逐层减半的值噪声之和，归一化到 [0, 1)
+pub fn fractal_noise(seed: u32, x: f32, y: f32, octaves: usize) -> f32 {
+    let (mut sum, mut amplitude, mut frequency, mut total) = (0.0, 1.0, 1.0, 0.0);
+    for octave in 0..octaves {
+        let layer_seed = seed.wrapping_add((octave as u32).wrapping_mul(0x9E37_79B9));
+        sum += value_noise(layer_seed, x * frequency, y * frequency) * amplitude;
+        total += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    if total > 0.0 { sum / total } else { 0.0 }
+}
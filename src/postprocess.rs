@@ -0,0 +1,80 @@
+// 后处理：作用在整张 FrameBuffer 上的各种效果
+pub mod bloom;
+pub mod dither;
+pub mod dof;
+pub mod glitch;
+pub mod grading;
+pub mod halftone;
+pub mod kuwahara;
+pub mod lut;
+pub mod motion_blur;
+pub mod outline;
+pub mod palette;
+pub mod pixel_art;
+pub mod ssao;
+pub mod tonemap;
+pub mod watercolor;
+
+use cgmath::{Matrix4 as Mat4, Vector3 as Vec3};
+use rayon::prelude::*;
+
+use crate::framebuffer::FrameBuffer;
+
+// 后处理时可用的场景信息
+pub struct PostContext {
+    pub proj: Mat4<f32>, // 投影矩阵，用于由深度缓冲求线性深度
+    pub frame: usize,    // 当前帧号，用于随时间变化的效果
+}
+
+// 一个后处理 pass：从 FrameBuffer 的各个缓冲（颜色、深度、法线、运动矢量等）读取数据，
+// 把结果写回颜色缓冲；按场景配置中的顺序依次执行
+pub trait PostProcess {
+    // 打印耗时用的名字
+    fn name(&self) -> &str;
+    fn apply(&self, framebuffer: &mut FrameBuffer, context: &PostContext);
+}
+
+// 由深度缓冲中的 [0, 1] 深度求出沿视线方向的距离
+pub fn linear_depth(proj: &Mat4<f32>, depth: f32) -> f32 {
+    let ndc_z = depth * 2.0 - 1.0;
+    // 投影矩阵满足 ndc_z = (c * z + d) / -z，其中 z 为观察空间深度（朝 -Z 看）
+    proj.w.z / (ndc_z + proj.z.z)
+}
+
+// 可分离的高斯模糊，越界时钳制到边缘；sigma 以像素为单位
+pub fn gaussian_blur(
+    data: &[Vec3<f32>],
+    width: usize,
+    height: usize,
+    sigma: f32,
+) -> Vec<Vec3<f32>> {
+    let radius = (sigma * 3.0).ceil() as isize;
+    if radius <= 0 {
+        return data.to_vec();
+    }
+    let kernel: Vec<f32> = (-radius..=radius)
+        .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = kernel.iter().sum();
+
+    let blur = |source: &[Vec3<f32>], horizontal: bool| -> Vec<Vec3<f32>> {
+        (0..width * height)
+            .into_par_iter()
+            .map(|idx| {
+                let (x, y) = ((idx % width) as isize, (idx / width) as isize);
+                let mut sum = Vec3::new(0.0, 0.0, 0.0);
+                for (k, weight) in kernel.iter().enumerate() {
+                    let offset = k as isize - radius;
+                    let (sx, sy) = if horizontal {
+                        ((x + offset).clamp(0, width as isize - 1), y)
+                    } else {
+                        (x, (y + offset).clamp(0, height as isize - 1))
+                    };
+                    sum += source[sy as usize * width + sx as usize] * *weight;
+                }
+                sum / total
+            })
+            .collect()
+    };
+    blur(&blur(data, true), false)
+}
@@ -0,0 +1,154 @@
+use cgmath::Vector3 as Vec3;
+
+use crate::framebuffer::FrameBuffer;
+use crate::postprocess::{PostContext, PostProcess};
+
+// 泛光：提取画面中超过阈值的高亮部分，经过逐级降采样/升采样模糊后叠加回原图
+// 需要在色调映射之前、描边之前对 HDR 画面执行
+pub struct Bloom {
+    pub threshold: f32, // 亮度超过该值的部分才会发光
+    pub knee: f32,      // 阈值附近的柔和过渡宽度
+    pub intensity: f32, // 叠加回原图时的强度
+    pub radius: f32,    // 升采样时的滤波半径（以当前级纹素为单位），越大光晕越散
+    pub levels: usize,  // 降采样的级数
+}
+
+// 模糊链中使用的单级缓冲
+struct Level {
+    width: usize,
+    height: usize,
+    data: Vec<Vec3<f32>>,
+}
+
+impl Level {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![Vec3::new(0.0, 0.0, 0.0); width * height],
+        }
+    }
+
+    // 以 [0, 1] 的归一化坐标做双线性采样，越界时钳制到边缘
+    fn sample(&self, u: f32, v: f32) -> Vec3<f32> {
+        let fx = (u * self.width as f32 - 0.5).clamp(0.0, (self.width - 1) as f32);
+        let fy = (v * self.height as f32 - 0.5).clamp(0.0, (self.height - 1) as f32);
+        let x0 = fx.floor() as usize;
+        let y0 = fy.floor() as usize;
+        let x1 = (x0 + 1).min(self.width - 1);
+        let y1 = (y0 + 1).min(self.height - 1);
+        let tx = fx - x0 as f32;
+        let ty = fy - y0 as f32;
+        let at = |x: usize, y: usize| self.data[y * self.width + x];
+        let top = at(x0, y0) * (1.0 - tx) + at(x1, y0) * tx;
+        let bottom = at(x0, y1) * (1.0 - tx) + at(x1, y1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+
+    // 3x3 帐篷滤波采样，offset 为采样间距（归一化坐标）
+    fn sample_tent(&self, u: f32, v: f32, offset_u: f32, offset_v: f32) -> Vec3<f32> {
+        let mut sum = Vec3::new(0.0, 0.0, 0.0);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let weight = match (dx == 0, dy == 0) {
+                    (true, true) => 4.0,
+                    (true, false) | (false, true) => 2.0,
+                    _ => 1.0,
+                };
+                sum += self.sample(u + dx as f32 * offset_u, v + dy as f32 * offset_v) * weight;
+            }
+        }
+        sum / 16.0
+    }
+
+    // 降采样到一半大小
+    fn downsample(&self) -> Level {
+        let mut next = Level::new((self.width / 2).max(1), (self.height / 2).max(1));
+        let (offset_u, offset_v) = (1.0 / self.width as f32, 1.0 / self.height as f32);
+        for y in 0..next.height {
+            for x in 0..next.width {
+                let u = (x as f32 + 0.5) / next.width as f32;
+                let v = (y as f32 + 0.5) / next.height as f32;
+                next.data[y * next.width + x] = self.sample_tent(u, v, offset_u, offset_v);
+            }
+        }
+        next
+    }
+}
+
+impl PostProcess for Bloom {
+    fn name(&self) -> &str {
+        "泛光"
+    }
+
+    fn apply(&self, framebuffer: &mut FrameBuffer, _context: &PostContext) {
+        let width = framebuffer.width;
+        let height = framebuffer.height;
+        if width < 2 || height < 2 || self.intensity <= 0.0 {
+            return;
+        }
+
+        // 1. 亮部提取
+        let mut bright = Level::new(width, height);
+        for (dst, src) in bright.data.iter_mut().zip(framebuffer.data.iter()) {
+            *dst = self.bright_pass(src.truncate());
+        }
+
+        // 2. 逐级降采样
+        let mut chain = vec![bright.downsample()];
+        while chain.len() < self.levels.max(1) {
+            let last = &chain[chain.len() - 1];
+            if last.width < 2 || last.height < 2 {
+                break;
+            }
+            let next = last.downsample();
+            chain.push(next);
+        }
+
+        // 3. 从最小的一级开始升采样，逐级累加
+        for i in (0..chain.len() - 1).rev() {
+            let (larger, smaller) = chain.split_at_mut(i + 1);
+            let target = &mut larger[i];
+            let source = &smaller[0];
+            let offset_u = self.radius / source.width as f32;
+            let offset_v = self.radius / source.height as f32;
+            for y in 0..target.height {
+                for x in 0..target.width {
+                    let u = (x as f32 + 0.5) / target.width as f32;
+                    let v = (y as f32 + 0.5) / target.height as f32;
+                    target.data[y * target.width + x] +=
+                        source.sample_tent(u, v, offset_u, offset_v);
+                }
+            }
+        }
+
+        // 4. 叠加回原图
+        let glow = &chain[0];
+        let offset_u = self.radius / glow.width as f32;
+        let offset_v = self.radius / glow.height as f32;
+        let normalize = self.intensity / chain.len() as f32;
+        for y in 0..height {
+            for x in 0..width {
+                let u = (x as f32 + 0.5) / width as f32;
+                let v = (y as f32 + 0.5) / height as f32;
+                let add = glow.sample_tent(u, v, offset_u, offset_v) * normalize;
+                let pixel = &mut framebuffer.data[y * width + x];
+                pixel.x += add.x;
+                pixel.y += add.y;
+                pixel.z += add.z;
+            }
+        }
+    }
+}
+
+impl Bloom {
+    // 带柔和过渡的亮度阈值
+    fn bright_pass(&self, color: Vec3<f32>) -> Vec3<f32> {
+        let brightness = color.x.max(color.y).max(color.z);
+        let knee = self.knee.max(1e-5);
+        let soft = (brightness - self.threshold + knee).clamp(0.0, 2.0 * knee);
+        let soft = soft * soft / (4.0 * knee);
+        let contribution = soft.max(brightness - self.threshold) / brightness.max(1e-5);
+        color * contribution
+    }
+}
@@ -0,0 +1,153 @@
+use cgmath::Vector3 as Vec3;
+use rayon::prelude::*;
+
+use crate::color::{linear_to_srgb, srgb_to_linear};
+use crate::framebuffer::FrameBuffer;
+use crate::postprocess::palette::Palette;
+use crate::postprocess::{PostContext, PostProcess};
+
+// 抖动方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DitherMethod {
+    Bayer(usize),   // 有序抖动，参数为 Bayer 矩阵边长（2 的幂）
+    FloydSteinberg, // 误差扩散，按蛇形顺序逐行扫描
+}
+
+// 抖动：把画面量化到调色板中的颜色。pixel_size 大于 1 时先按块取平均，
+// 得到更粗的像素，并让图案在 SSAA 降采样后仍然清晰
+pub struct Dither {
+    pub method: DitherMethod,
+    pub palette: Palette,
+    pub spread: f32, // 有序抖动时阈值扰动的幅度（sRGB 单位），通常取调色板相邻颜色的间距
+    pub pixel_size: usize,
+}
+
+impl PostProcess for Dither {
+    fn name(&self) -> &str {
+        match self.method {
+            DitherMethod::Bayer(_) => "有序抖动",
+            DitherMethod::FloydSteinberg => "误差扩散抖动",
+        }
+    }
+
+    fn apply(&self, framebuffer: &mut FrameBuffer, _context: &PostContext) {
+        if self.palette.colors.is_empty() {
+            return;
+        }
+        let size = self.pixel_size.max(1);
+        let blocks_x = framebuffer.width.div_ceil(size);
+        let blocks_y = framebuffer.height.div_ceil(size);
+        let blocks = block_average(framebuffer, size, blocks_x, blocks_y);
+
+        let quantized = match self.method {
+            DitherMethod::Bayer(n) => self.bayer(&blocks, blocks_x, n),
+            DitherMethod::FloydSteinberg => self.floyd_steinberg(blocks, blocks_x, blocks_y),
+        };
+
+        let width = framebuffer.width;
+        for (idx, pixel) in framebuffer.data.iter_mut().enumerate() {
+            let (x, y) = (idx % width, idx / width);
+            let c = quantized[(y / size) * blocks_x + x / size];
+            *pixel = c.map(srgb_to_linear).extend(pixel.w);
+        }
+    }
+}
+
+impl Dither {
+    fn bayer(&self, blocks: &[Vec3<f32>], blocks_x: usize, n: usize) -> Vec<Vec3<f32>> {
+        let matrix = bayer_matrix(n);
+        let n = (matrix.len() as f32).sqrt() as usize;
+        blocks
+            .par_iter()
+            .enumerate()
+            .map(|(idx, &c)| {
+                let (x, y) = (idx % blocks_x, idx / blocks_x);
+                let threshold = matrix[(y % n) * n + x % n] - 0.5;
+                let offset = self.spread * threshold;
+                self.palette.nearest(c + Vec3::new(offset, offset, offset))
+            })
+            .collect()
+    }
+
+    fn floyd_steinberg(
+        &self,
+        mut blocks: Vec<Vec3<f32>>,
+        width: usize,
+        height: usize,
+    ) -> Vec<Vec3<f32>> {
+        for y in 0..height {
+            // 蛇形扫描，避免误差总是朝同一方向累积出斜纹
+            let reverse = y % 2 == 1;
+            for i in 0..width {
+                let x = if reverse { width - 1 - i } else { i };
+                let idx = y * width + x;
+                let old = blocks[idx];
+                let new = self.palette.nearest(old);
+                blocks[idx] = new;
+                let error = old - new;
+
+                let forward: isize = if reverse { -1 } else { 1 };
+                let mut spread = |dx: isize, dy: usize, weight: f32| {
+                    let nx = x as isize + dx;
+                    if nx < 0 || nx >= width as isize || y + dy >= height {
+                        return;
+                    }
+                    blocks[(y + dy) * width + nx as usize] += error * weight;
+                };
+                spread(forward, 0, 7.0 / 16.0);
+                spread(-forward, 1, 3.0 / 16.0);
+                spread(0, 1, 5.0 / 16.0);
+                spread(forward, 1, 1.0 / 16.0);
+            }
+        }
+        blocks
+    }
+}
+
+// 按 size x size 的块求平均颜色，返回 sRGB 编码的结果
+pub fn block_average(
+    framebuffer: &FrameBuffer,
+    size: usize,
+    blocks_x: usize,
+    blocks_y: usize,
+) -> Vec<Vec3<f32>> {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    (0..blocks_x * blocks_y)
+        .into_par_iter()
+        .map(|idx| {
+            let (bx, by) = (idx % blocks_x, idx / blocks_x);
+            let mut sum = Vec3::new(0.0, 0.0, 0.0);
+            let mut count = 0.0;
+            for y in by * size..((by + 1) * size).min(height) {
+                for x in bx * size..((bx + 1) * size).min(width) {
+                    sum += framebuffer.data[y * width + x].truncate();
+                    count += 1.0;
+                }
+            }
+            (sum / count).map(|v| linear_to_srgb(v).clamp(0.0, 1.0))
+        })
+        .collect()
+}
+
+// n x n 的 Bayer 阈值矩阵（n 向上取到 2 的幂），取值为 (0, 1) 内均匀分布的阈值
+pub fn bayer_matrix(n: usize) -> Vec<f32> {
+    let n = n.max(2).next_power_of_two();
+    let mut matrix = vec![0usize];
+    let mut size = 1;
+    while size < n {
+        let mut next = vec![0usize; size * size * 4];
+        for y in 0..size {
+            for x in 0..size {
+                let v = matrix[y * size + x] * 4;
+                next[y * size * 2 + x] = v;
+                next[y * size * 2 + x + size] = v + 2;
+                next[(y + size) * size * 2 + x] = v + 3;
+                next[(y + size) * size * 2 + x + size] = v + 1;
+            }
+        }
+        matrix = next;
+        size *= 2;
+    }
+    let count = (n * n) as f32;
+    matrix.iter().map(|&v| (v as f32 + 0.5) / count).collect()
+}
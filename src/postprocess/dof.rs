@@ -0,0 +1,196 @@
+use cgmath::Vector3 as Vec3;
+use rayon::prelude::*;
+
+use crate::framebuffer::FrameBuffer;
+use crate::postprocess::{PostContext, PostProcess, linear_depth};
+
+// 每个像素的聚集采样数
+const GATHER_SAMPLES: usize = 48;
+// 统计前景最大模糊半径时使用的分块大小（像素）
+const TILE_SIZE: usize = 16;
+
+// 景深：按薄透镜模型由深度、对焦距离与光圈计算每个像素的弥散圆 (CoC)，
+// 再对背景和前景分别做聚集模糊，最后把前景叠加在背景之上
+pub struct DepthOfField {
+    pub focus_distance: f32, // 对焦距离（世界单位，沿视线方向）
+    pub f_stop: f32,         // 光圈 f 值，越小虚化越强
+    pub focal_length: f32,   // 焦距 (mm)
+    pub sensor_height: f32,  // 底片高度 (mm)
+    pub unit_scale: f32,     // 一个世界单位对应的米数
+    pub max_coc: f32,        // 弥散圆半径上限（像素）
+}
+
+impl PostProcess for DepthOfField {
+    fn name(&self) -> &str {
+        "景深"
+    }
+
+    fn apply(&self, framebuffer: &mut FrameBuffer, context: &PostContext) {
+        let proj = &context.proj;
+        let width = framebuffer.width;
+        let height = framebuffer.height;
+
+        // 带符号的弥散圆半径：负数为前景（比焦平面近），正数为背景
+        let coc: Vec<f32> = framebuffer
+            .depth
+            .par_iter()
+            .map(|&depth| self.circle_of_confusion(linear_depth(proj, depth), height))
+            .collect();
+        let colors: Vec<Vec3<f32>> = framebuffer.data.iter().map(|c| c.truncate()).collect();
+        let near_radius = near_tile_max(&coc, width, height);
+
+        let kernel: Vec<(f32, f32)> = (0..GATHER_SAMPLES).map(vogel_disk).collect();
+        let image = Image {
+            width,
+            height,
+            colors: &colors,
+            coc: &coc,
+        };
+
+        let result: Vec<Vec3<f32>> = (0..width * height)
+            .into_par_iter()
+            .map(|idx| {
+                let (x, y) = (idx % width, idx / width);
+                let far = image.gather_far(x, y, &kernel);
+                let radius =
+                    near_radius[(y / TILE_SIZE) * width.div_ceil(TILE_SIZE) + x / TILE_SIZE];
+                match image.gather_near(x, y, radius, &kernel) {
+                    Some((near, alpha)) => far * (1.0 - alpha) + near * alpha,
+                    None => far,
+                }
+            })
+            .collect();
+
+        for (pixel, color) in framebuffer.data.iter_mut().zip(result) {
+            *pixel = color.extend(pixel.w);
+        }
+    }
+}
+
+impl DepthOfField {
+    // 薄透镜公式：c = A * f * |S - S_focus| / (S * (S_focus - f))，A = f / N，再换算为像素
+    fn circle_of_confusion(&self, distance: f32, image_height: usize) -> f32 {
+        let f = self.focal_length / 1000.0;
+        let aperture = f / self.f_stop.max(0.1);
+        let focus = (self.focus_distance * self.unit_scale).max(f * 1.01);
+        let s = (distance * self.unit_scale).max(1e-4);
+        let coc = aperture * f * (s - focus) / (s * (focus - f));
+        let pixels = coc * 1000.0 / self.sensor_height * image_height as f32 * 0.5;
+        pixels.clamp(-self.max_coc, self.max_coc)
+    }
+}
+
+struct Image<'a> {
+    width: usize,
+    height: usize,
+    colors: &'a [Vec3<f32>],
+    coc: &'a [f32],
+}
+
+impl Image<'_> {
+    fn index(&self, x: usize, y: usize, (dx, dy): (f32, f32)) -> Option<usize> {
+        let sx = (x as f32 + dx).round();
+        let sy = (y as f32 + dy).round();
+        if sx < 0.0 || sy < 0.0 || sx >= self.width as f32 || sy >= self.height as f32 {
+            return None;
+        }
+        Some(sy as usize * self.width + sx as usize)
+    }
+
+    // 背景：在自身弥散圆内聚集，只接受同样处于背景、且模糊范围能覆盖到当前像素的样本，
+    // 这样清晰的前景物体不会被抹进背景里
+    fn gather_far(&self, x: usize, y: usize, kernel: &[(f32, f32)]) -> Vec3<f32> {
+        let idx = y * self.width + x;
+        let radius = self.coc[idx];
+        if radius < 0.5 {
+            return self.colors[idx];
+        }
+        let mut sum = self.colors[idx];
+        let mut weight_sum = 1.0;
+        for &(kx, ky) in kernel {
+            let offset = (kx * radius, ky * radius);
+            let Some(s) = self.index(x, y, offset) else {
+                continue;
+            };
+            let sample_coc = self.coc[s];
+            if sample_coc <= 0.0 {
+                continue;
+            }
+            let distance = (offset.0 * offset.0 + offset.1 * offset.1).sqrt();
+            let weight = (sample_coc - distance + 1.0).clamp(0.0, 1.0);
+            sum += self.colors[s] * weight;
+            weight_sum += weight;
+        }
+        sum / weight_sum
+    }
+
+    // 前景：以周围前景的最大弥散圆为搜索半径，收集模糊范围能扩散到当前像素的前景样本，
+    // 返回 (前景颜色, 覆盖度)；前景模糊会盖住焦平面上的清晰物体
+    fn gather_near(
+        &self,
+        x: usize,
+        y: usize,
+        radius: f32,
+        kernel: &[(f32, f32)],
+    ) -> Option<(Vec3<f32>, f32)> {
+        if radius < 0.5 {
+            return None;
+        }
+        let mut sum = Vec3::new(0.0, 0.0, 0.0);
+        let mut weight_sum = 0.0;
+        for &(kx, ky) in kernel {
+            let offset = (kx * radius, ky * radius);
+            let Some(s) = self.index(x, y, offset) else {
+                continue;
+            };
+            let sample_coc = -self.coc[s];
+            if sample_coc <= 0.0 {
+                continue;
+            }
+            let distance = (offset.0 * offset.0 + offset.1 * offset.1).sqrt();
+            let weight = (sample_coc - distance + 1.0).clamp(0.0, 1.0);
+            sum += self.colors[s] * weight;
+            weight_sum += weight;
+        }
+        if weight_sum <= 1e-4 {
+            return None;
+        }
+        // 物体边缘处大约一半的样本命中，乘 2 使前景内部完全不透明、向外逐渐过渡
+        let alpha = (weight_sum / kernel.len() as f32 * 2.0).clamp(0.0, 1.0);
+        Some((sum / weight_sum, alpha))
+    }
+}
+
+// 每个分块内前景弥散圆的最大半径，并向相邻分块扩张一格，保证前景能模糊到物体外侧
+fn near_tile_max(coc: &[f32], width: usize, height: usize) -> Vec<f32> {
+    let tiles_x = width.div_ceil(TILE_SIZE);
+    let tiles_y = height.div_ceil(TILE_SIZE);
+    let mut tiles = vec![0.0f32; tiles_x * tiles_y];
+    for y in 0..height {
+        for x in 0..width {
+            let tile = &mut tiles[(y / TILE_SIZE) * tiles_x + x / TILE_SIZE];
+            *tile = tile.max(-coc[y * width + x]);
+        }
+    }
+    let mut dilated = vec![0.0f32; tiles_x * tiles_y];
+    for ty in 0..tiles_y {
+        for tx in 0..tiles_x {
+            let mut max = 0.0f32;
+            for ny in ty.saturating_sub(1)..=(ty + 1).min(tiles_y - 1) {
+                for nx in tx.saturating_sub(1)..=(tx + 1).min(tiles_x - 1) {
+                    max = max.max(tiles[ny * tiles_x + nx]);
+                }
+            }
+            dilated[ty * tiles_x + tx] = max;
+        }
+    }
+    dilated
+}
+
+// Vogel 螺旋：在单位圆盘内均匀分布的采样点
+fn vogel_disk(i: usize) -> (f32, f32) {
+    const GOLDEN_ANGLE: f32 = 2.399_963;
+    let r = ((i as f32 + 0.5) / GATHER_SAMPLES as f32).sqrt();
+    let theta = i as f32 * GOLDEN_ANGLE;
+    (r * theta.cos(), r * theta.sin())
+}
@@ -0,0 +1,152 @@
+use cgmath::Vector3 as Vec3;
+use rayon::prelude::*;
+
+use crate::framebuffer::FrameBuffer;
+use crate::noise::random;
+use crate::postprocess::{PostContext, PostProcess};
+
+// 故障效果：RGB 通道错位、水平切片位移、块状损坏、扫描线与噪点
+// 所有随机数都由 seed 和帧号哈希得到，同一配置重复渲染结果相同；
+// 切片与损坏块每 hold_frames 帧换一次位置，噪点每帧都变
+pub struct Glitch {
+    pub seed: u32,
+    pub intensity: f32,       // 总体强度，0 为无效果，1 为配置的最大值
+    pub rgb_shift: f32,       // 红、蓝通道相对绿通道的最大偏移（像素）
+    pub slice_count: usize,   // 发生水平位移的切片数量
+    pub slice_shift: f32,     // 切片最大水平位移（像素）
+    pub block_size: usize,    // 损坏块的边长（像素）
+    pub block_chance: f32,    // 每个块被损坏的概率
+    pub scanline: f32,        // 扫描线暗部的压暗程度，0 ~ 1
+    pub scanline_period: f32, // 扫描线周期（像素）
+    pub noise: f32,           // 噪点幅度
+    pub hold_frames: usize,   // 切片与损坏块保持不变的帧数
+}
+
+// 损坏块的几种表现
+#[derive(Clone, Copy)]
+enum Corruption {
+    None,
+    Displace(isize, isize), // 显示别处的画面
+    ChannelSwap,            // 通道互换
+    Posterize,              // 色阶断层
+}
+
+impl PostProcess for Glitch {
+    fn name(&self) -> &str {
+        "故障效果"
+    }
+
+    fn apply(&self, framebuffer: &mut FrameBuffer, context: &PostContext) {
+        let frame = context.frame;
+        let width = framebuffer.width;
+        let height = framebuffer.height;
+        let intensity = self.intensity.clamp(0.0, 1.0);
+        if intensity <= 0.0 {
+            return;
+        }
+
+        let colors: Vec<Vec3<f32>> = framebuffer.data.iter().map(|c| c.truncate()).collect();
+        // 保持期内的随机种子与逐帧随机种子
+        let period = (frame / self.hold_frames.max(1)) as u32;
+        let held = self.seed ^ period.wrapping_mul(0x68E3_1DA4);
+        let per_frame = self.seed ^ (frame as u32).wrapping_mul(0xB529_7A4D);
+        // 每个保持期的强度随机起伏，动画中时强时弱
+        let intensity = intensity * (0.3 + 0.7 * random(held, 0, 0));
+
+        let row_shift = self.row_shift(height, held, intensity);
+        let block_size = self.block_size.max(1);
+        let blocks_x = width.div_ceil(block_size);
+        let blocks_y = height.div_ceil(block_size);
+        let blocks: Vec<Corruption> = (0..blocks_x * blocks_y)
+            .map(|i| self.corruption(held, i as u32, intensity, blocks_x, blocks_y))
+            .collect();
+
+        // 通道错位方向每个保持期变化一次，长度随强度增大
+        let angle = random(held, 1, 0) * std::f32::consts::TAU;
+        let shift = self.rgb_shift * intensity * (0.5 + 0.5 * random(held, 2, 0));
+        let (dx, dy) = (angle.cos() * shift, angle.sin() * shift * 0.25);
+
+        let fetch = |x: f32, y: f32| -> Vec3<f32> {
+            // 水平方向回绕，如同信号错位；竖直方向截断
+            let sx = (x.round() as isize).rem_euclid(width as isize) as usize;
+            let sy = (y.round() as isize).clamp(0, height as isize - 1) as usize;
+            colors[sy * width + sx]
+        };
+
+        let result: Vec<Vec3<f32>> = (0..width * height)
+            .into_par_iter()
+            .map(|idx| {
+                let (x, y) = (idx % width, idx / width);
+                let block = blocks[(y / block_size) * blocks_x + x / block_size];
+                let (mut sx, mut sy) = (x as f32 + row_shift[y], y as f32);
+                if let Corruption::Displace(bx, by) = block {
+                    sx += (bx * block_size as isize) as f32;
+                    sy += (by * block_size as isize) as f32;
+                }
+                // 位移过的切片上色散更明显
+                let split = if row_shift[y] != 0.0 { 2.0 } else { 1.0 };
+                let mut color = Vec3::new(
+                    fetch(sx + dx * split, sy + dy * split).x,
+                    fetch(sx, sy).y,
+                    fetch(sx - dx * split, sy - dy * split).z,
+                );
+
+                match block {
+                    Corruption::ChannelSwap => color = Vec3::new(color.z, color.x, color.y),
+                    Corruption::Posterize => {
+                        color = color.map(|c| (c.clamp(0.0, 1.0) * 3.0).round() / 3.0)
+                    }
+                    Corruption::None | Corruption::Displace(..) => {}
+                }
+
+                let phase = y as f32 / self.scanline_period.max(1.0) * std::f32::consts::TAU;
+                let scanline = 1.0 - self.scanline * intensity * (0.5 - 0.5 * phase.cos());
+                let noise = (random(per_frame, x as u32, y as u32) - 0.5) * self.noise * intensity;
+                color.map(|c| (c * scanline + noise).max(0.0))
+            })
+            .collect();
+
+        for (pixel, color) in framebuffer.data.iter_mut().zip(result) {
+            *pixel = color.extend(pixel.w);
+        }
+    }
+}
+
+impl Glitch {
+    // 每一行的水平位移：随机选取若干条高度不一的切片
+    fn row_shift(&self, height: usize, seed: u32, intensity: f32) -> Vec<f32> {
+        let mut shift = vec![0.0; height];
+        for i in 0..self.slice_count as u32 {
+            let start = (random(seed, 100, i) * height as f32) as usize;
+            let size = ((0.01 + 0.07 * random(seed, 101, i)) * height as f32) as usize;
+            let offset = (random(seed, 102, i) * 2.0 - 1.0) * self.slice_shift * intensity;
+            for row in shift.iter_mut().skip(start).take(size.max(1)) {
+                *row = offset;
+            }
+        }
+        shift
+    }
+
+    fn corruption(
+        &self,
+        seed: u32,
+        block: u32,
+        intensity: f32,
+        blocks_x: usize,
+        blocks_y: usize,
+    ) -> Corruption {
+        if random(seed, 200, block) >= self.block_chance * intensity {
+            return Corruption::None;
+        }
+        let pick = random(seed, 201, block);
+        if pick < 0.5 {
+            let bx = ((random(seed, 202, block) * 2.0 - 1.0) * blocks_x as f32 * 0.25) as isize;
+            let by = ((random(seed, 203, block) * 2.0 - 1.0) * blocks_y as f32 * 0.1) as isize;
+            Corruption::Displace(bx, by)
+        } else if pick < 0.75 {
+            Corruption::ChannelSwap
+        } else {
+            Corruption::Posterize
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use cgmath::{ElementWise, Vector3 as Vec3};
+
+use crate::color::{linear_to_srgb, luminance, srgb_to_linear};
+use crate::framebuffer::FrameBuffer;
+use crate::postprocess::lut::{Lut3D, LutInterpolation};
+use crate::postprocess::{PostContext, PostProcess};
+
+// 调色：在色调映射之后执行，与调色软件一样作用在 sRGB 编码后的显示信号上，
+// 依次为 lift/gamma/gain、饱和度、3D LUT，最后再解码回线性空间
+pub struct ColorGrading {
+    pub lift: Vec3<f32>,  // 抬高暗部，0 为不变
+    pub gamma: Vec3<f32>, // 中间调，大于 1 变亮
+    pub gain: Vec3<f32>,  // 各通道增益，例如 (0.9, 0.9, 1.1) 可以中和偏黄的画面
+    pub saturation: f32,  // 饱和度，1 为不变，0 为黑白
+    pub lut: Option<Lut3D>,
+    pub lut_interpolation: LutInterpolation,
+}
+
+impl PostProcess for ColorGrading {
+    fn name(&self) -> &str {
+        "调色"
+    }
+
+    fn apply(&self, framebuffer: &mut FrameBuffer, _context: &PostContext) {
+        for pixel in framebuffer.data.iter_mut() {
+            let c = pixel.truncate();
+            let encoded = Vec3::new(
+                linear_to_srgb(c.x),
+                linear_to_srgb(c.y),
+                linear_to_srgb(c.z),
+            );
+            let graded = self.grade(encoded);
+            let decoded = Vec3::new(
+                srgb_to_linear(graded.x),
+                srgb_to_linear(graded.y),
+                srgb_to_linear(graded.z),
+            );
+            *pixel = decoded.extend(pixel.w);
+        }
+    }
+}
+
+impl ColorGrading {
+    fn grade(&self, c: Vec3<f32>) -> Vec3<f32> {
+        let one = Vec3::new(1.0, 1.0, 1.0);
+        // out = (gain * (x + lift * (1 - x))) ^ (1 / gamma)
+        let lifted = c + self.lift.mul_element_wise(one - c);
+        let gained = lifted.mul_element_wise(self.gain);
+        let power = |v: f32, gamma: f32| v.max(0.0).powf(1.0 / gamma.max(1e-3));
+        let c = Vec3::new(
+            power(gained.x, self.gamma.x),
+            power(gained.y, self.gamma.y),
+            power(gained.z, self.gamma.z),
+        );
+
+        let gray = luminance(c);
+        let c = Vec3::new(gray, gray, gray) + (c - Vec3::new(gray, gray, gray)) * self.saturation;
+        let c = Vec3::new(
+            c.x.clamp(0.0, 1.0),
+            c.y.clamp(0.0, 1.0),
+            c.z.clamp(0.0, 1.0),
+        );
+
+        match &self.lut {
+            Some(lut) => lut.sample(c, self.lut_interpolation),
+            None => c,
+        }
+    }
+}
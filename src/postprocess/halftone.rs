@@ -0,0 +1,108 @@
+use cgmath::Vector3 as Vec3;
+use rayon::prelude::*;
+
+use crate::color::{linear_to_srgb, srgb_to_linear};
+use crate::framebuffer::FrameBuffer;
+use crate::postprocess::{PostContext, PostProcess};
+
+// CMYK 半调网点：把画面分解为青、品红、黄、黑四个通道，每个通道在各自旋转的网格上
+// 画出面积与浓度成正比的圆点，再按减色混合叠印在纸上
+pub struct Halftone {
+    pub cell_size: f32,       // 网格间距（像素）
+    pub angles: [f32; 4],     // C / M / Y / K 的网角（度）
+    pub inks: [Vec3<f32>; 4], // C / M / Y / K 的油墨颜色（sRGB 编码）
+    pub paper: Vec3<f32>,     // 纸张颜色（sRGB 编码）
+}
+
+impl PostProcess for Halftone {
+    fn name(&self) -> &str {
+        "半调网点"
+    }
+
+    fn apply(&self, framebuffer: &mut FrameBuffer, _context: &PostContext) {
+        let width = framebuffer.width;
+        let height = framebuffer.height;
+        if self.cell_size < 1.0 {
+            return;
+        }
+        let cmyk: Vec<[f32; 4]> = framebuffer
+            .data
+            .par_iter()
+            .map(|c| rgb_to_cmyk(c.truncate().map(|v| linear_to_srgb(v).clamp(0.0, 1.0))))
+            .collect();
+        // 减色混合在线性空间中相乘
+        let inks = self.inks.map(|ink| ink.map(srgb_to_linear));
+        let paper = self.paper.map(srgb_to_linear);
+
+        let result: Vec<Vec3<f32>> = (0..width * height)
+            .into_par_iter()
+            .map(|idx| {
+                let (x, y) = ((idx % width) as f32 + 0.5, (idx / width) as f32 + 0.5);
+                let mut color = paper;
+                for (channel, ink) in inks.iter().enumerate() {
+                    if self.covered(&cmyk, width, height, channel, x, y) {
+                        color = Vec3::new(color.x * ink.x, color.y * ink.y, color.z * ink.z);
+                    }
+                }
+                color
+            })
+            .collect();
+
+        for (pixel, color) in framebuffer.data.iter_mut().zip(result) {
+            *pixel = color.extend(pixel.w);
+        }
+    }
+}
+
+impl Halftone {
+    // 像素 (x, y) 是否落在某个通道的网点内。浓度高时网点会超出自身格子，
+    // 因此要检查周围 3x3 个格子的网点
+    fn covered(
+        &self,
+        cmyk: &[[f32; 4]],
+        width: usize,
+        height: usize,
+        channel: usize,
+        x: f32,
+        y: f32,
+    ) -> bool {
+        let (sin, cos) = self.angles[channel].to_radians().sin_cos();
+        // 旋转到网格坐标系
+        let u = (x * cos + y * sin) / self.cell_size;
+        let v = (-x * sin + y * cos) / self.cell_size;
+        let (cu, cv) = (u.floor(), v.floor());
+        for dv in -1..=1 {
+            for du in -1..=1 {
+                let (center_u, center_v) = (cu + du as f32 + 0.5, cv + dv as f32 + 0.5);
+                // 网点中心转回屏幕坐标，取该处的浓度
+                let sx = (center_u * cos - center_v * sin) * self.cell_size;
+                let sy = (center_u * sin + center_v * cos) * self.cell_size;
+                let px = (sx.floor() as isize).clamp(0, width as isize - 1) as usize;
+                let py = (sy.floor() as isize).clamp(0, height as isize - 1) as usize;
+                let density = cmyk[py * width + px][channel];
+                // 圆点面积占格子面积的比例等于浓度
+                let radius = (density / std::f32::consts::PI).sqrt();
+                let (du, dv) = (u - center_u, v - center_v);
+                if du * du + dv * dv < radius * radius {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+// sRGB 颜色分解为 CMYK 浓度，黑色通道取三者共同的部分
+fn rgb_to_cmyk(c: Vec3<f32>) -> [f32; 4] {
+    let k = 1.0 - c.x.max(c.y).max(c.z);
+    if k >= 1.0 {
+        return [0.0, 0.0, 0.0, 1.0];
+    }
+    let scale = 1.0 / (1.0 - k);
+    [
+        (1.0 - c.x - k) * scale,
+        (1.0 - c.y - k) * scale,
+        (1.0 - c.z - k) * scale,
+        k,
+    ]
+}
@@ -0,0 +1,190 @@
+use cgmath::{InnerSpace, Vector2 as Vec2, Vector3 as Vec3};
+use rayon::prelude::*;
+
+use crate::framebuffer::FrameBuffer;
+use crate::postprocess::{PostContext, PostProcess, gaussian_blur};
+
+// 扇区数量，多项式权重按 8 个扇区推导
+const SECTORS: usize = 8;
+
+// 各向异性 Kuwahara 滤波：由结构张量求出每个像素的边缘方向与各向异性程度，
+// 在沿边缘方向拉长的椭圆内分 8 个扇区求均值与方差，方差越小的扇区权重越大，
+// 得到平坦色块与保留边缘的油画笔触效果。应在色调映射之后的显示颜色上执行
+pub struct Kuwahara {
+    pub radius: f32,       // 滤波半径（像素）
+    pub sharpness: f32,    // 扇区权重的指数 q，越大边缘越锐利
+    pub alpha: f32,        // 椭圆的拉伸程度，越小越细长
+    pub tensor_sigma: f32, // 平滑结构张量的高斯半径（像素）
+}
+
+// 单个像素处的局部方向
+#[derive(Clone, Copy)]
+struct Orientation {
+    direction: Vec2<f32>, // 沿边缘（梯度垂直方向）的单位向量
+    anisotropy: f32,      // 0 为各向同性，接近 1 为明显的单一方向
+}
+
+impl PostProcess for Kuwahara {
+    fn name(&self) -> &str {
+        "Kuwahara 滤波"
+    }
+
+    fn apply(&self, framebuffer: &mut FrameBuffer, _context: &PostContext) {
+        let width = framebuffer.width;
+        let height = framebuffer.height;
+        if width < 3 || height < 3 || self.radius < 1.0 {
+            return;
+        }
+        let colors: Vec<Vec3<f32>> = framebuffer.data.iter().map(|c| c.truncate()).collect();
+        let orientation = self.orientation(&colors, width, height);
+
+        let filtered: Vec<Vec3<f32>> = (0..width * height)
+            .into_par_iter()
+            .map(|idx| self.filter(&colors, width, height, idx, orientation[idx]))
+            .collect();
+        for (pixel, color) in framebuffer.data.iter_mut().zip(filtered) {
+            *pixel = color.extend(pixel.w);
+        }
+    }
+}
+
+impl Kuwahara {
+    // 结构张量 (E, F, G) 经高斯平滑后做特征分解，得到每个像素的边缘方向与各向异性程度
+    fn orientation(&self, colors: &[Vec3<f32>], width: usize, height: usize) -> Vec<Orientation> {
+        let at = |x: isize, y: isize| {
+            let x = x.clamp(0, width as isize - 1) as usize;
+            let y = y.clamp(0, height as isize - 1) as usize;
+            colors[y * width + x]
+        };
+        let tensor: Vec<Vec3<f32>> = (0..width * height)
+            .into_par_iter()
+            .map(|idx| {
+                let (x, y) = ((idx % width) as isize, (idx / width) as isize);
+                let gx = (at(x + 1, y - 1) + at(x + 1, y) * 2.0 + at(x + 1, y + 1)
+                    - at(x - 1, y - 1)
+                    - at(x - 1, y) * 2.0
+                    - at(x - 1, y + 1))
+                    / 4.0;
+                let gy = (at(x - 1, y + 1) + at(x, y + 1) * 2.0 + at(x + 1, y + 1)
+                    - at(x - 1, y - 1)
+                    - at(x, y - 1) * 2.0
+                    - at(x + 1, y - 1))
+                    / 4.0;
+                Vec3::new(gx.dot(gx), gx.dot(gy), gy.dot(gy))
+            })
+            .collect();
+        let tensor = gaussian_blur(&tensor, width, height, self.tensor_sigma);
+
+        tensor
+            .par_iter()
+            .map(|t| {
+                let (e, f, g) = (t.x, t.y, t.z);
+                let root = ((e - g) * (e - g) + 4.0 * f * f).sqrt();
+                let lambda1 = 0.5 * (e + g + root);
+                let lambda2 = 0.5 * (e + g - root);
+                let v = Vec2::new(lambda1 - e, -f);
+                let direction = if v.magnitude2() > 0.0 {
+                    v.normalize()
+                } else {
+                    Vec2::new(0.0, 1.0)
+                };
+                let anisotropy = if lambda1 + lambda2 > 0.0 {
+                    (lambda1 - lambda2) / (lambda1 + lambda2)
+                } else {
+                    0.0
+                };
+                Orientation {
+                    direction,
+                    anisotropy,
+                }
+            })
+            .collect()
+    }
+
+    // 按 Kyprianidis 等人的多项式扇区权重在椭圆内加权，避免硬扇区边界带来的块状瑕疵
+    fn filter(
+        &self,
+        colors: &[Vec3<f32>],
+        width: usize,
+        height: usize,
+        idx: usize,
+        orientation: Orientation,
+    ) -> Vec3<f32> {
+        let (x, y) = ((idx % width) as isize, (idx / width) as isize);
+        let anisotropy = orientation.anisotropy;
+        // alpha 为 0 时平坦区域（各向异性为 0）会得到 0 / 0，取一个很小的正数代替
+        let alpha = self.alpha.max(1e-3);
+        let a = self.radius * ((alpha + anisotropy) / alpha).clamp(0.1, 2.0);
+        let b = self.radius * (alpha / (alpha + anisotropy)).clamp(0.1, 2.0);
+        let (cos_phi, sin_phi) = (orientation.direction.x, orientation.direction.y);
+        let max_x = (a * a * cos_phi * cos_phi + b * b * sin_phi * sin_phi).sqrt() as isize;
+        let max_y = (a * a * sin_phi * sin_phi + b * b * cos_phi * cos_phi).sqrt() as isize;
+
+        let zeta = 2.0 / self.radius;
+        let sector = std::f32::consts::PI / SECTORS as f32;
+        let eta = (zeta + sector.cos()) / (sector.sin() * sector.sin());
+
+        let mut mean = [Vec3::new(0.0, 0.0, 0.0); SECTORS];
+        let mut square = [Vec3::new(0.0, 0.0, 0.0); SECTORS];
+        let mut total = [0.0f32; SECTORS];
+        for j in -max_y..=max_y {
+            for i in -max_x..=max_x {
+                // 旋转到边缘方向后按椭圆半轴缩放，落在半径 0.5 的圆内的样本才参与
+                let (fi, fj) = (i as f32, j as f32);
+                let v = Vec2::new(
+                    0.5 * (cos_phi * fi + sin_phi * fj) / a,
+                    0.5 * (-sin_phi * fi + cos_phi * fj) / b,
+                );
+                if v.magnitude2() > 0.25 {
+                    continue;
+                }
+                let sx = (x + i).clamp(0, width as isize - 1) as usize;
+                let sy = (y + j).clamp(0, height as isize - 1) as usize;
+                let c = colors[sy * width + sx];
+
+                let mut w = [0.0f32; SECTORS];
+                let polynomial = |v: Vec2<f32>, w: &mut [f32; SECTORS], offset: usize| {
+                    let vxx = zeta - eta * v.x * v.x;
+                    let vyy = zeta - eta * v.y * v.y;
+                    w[offset] = (v.y + vxx).max(0.0).powi(2);
+                    w[offset + 2] = (-v.x + vyy).max(0.0).powi(2);
+                    w[offset + 4] = (-v.y + vxx).max(0.0).powi(2);
+                    w[offset + 6] = (v.x + vyy).max(0.0).powi(2);
+                };
+                polynomial(v, &mut w, 0);
+                let rotated = Vec2::new(v.x - v.y, v.x + v.y) * std::f32::consts::FRAC_1_SQRT_2;
+                polynomial(rotated, &mut w, 1);
+                let sum: f32 = w.iter().sum();
+                if sum <= 0.0 {
+                    continue;
+                }
+                let g = (-3.125 * v.magnitude2()).exp() / sum;
+                for k in 0..SECTORS {
+                    let wk = w[k] * g;
+                    mean[k] += c * wk;
+                    square[k] += Vec3::new(c.x * c.x, c.y * c.y, c.z * c.z) * wk;
+                    total[k] += wk;
+                }
+            }
+        }
+
+        let mut result = Vec3::new(0.0, 0.0, 0.0);
+        let mut weight_sum = 0.0;
+        for k in 0..SECTORS {
+            if total[k] <= 0.0 {
+                continue;
+            }
+            let m = mean[k] / total[k];
+            let s = square[k] / total[k] - Vec3::new(m.x * m.x, m.y * m.y, m.z * m.z);
+            let variance = s.x.abs() + s.y.abs() + s.z.abs();
+            let weight = 1.0 / (1.0 + (255.0 * variance).powf(0.5 * self.sharpness));
+            result += m * weight;
+            weight_sum += weight;
+        }
+        if weight_sum > 0.0 {
+            result / weight_sum
+        } else {
+            colors[idx]
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use cgmath::Vector3 as Vec3;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+// 3D LUT 的插值方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LutInterpolation {
+    Trilinear,   // 立方体 8 个顶点插值
+    Tetrahedral, // 把立方体切成 6 个四面体，只用其中 4 个顶点插值，灰阶与饱和色更准确
+}
+
+// Adobe / Resolve 格式 (.cube) 的 3D 查找表
+pub struct Lut3D {
+    size: usize,
+    domain_min: Vec3<f32>,
+    domain_max: Vec3<f32>,
+    data: Vec<Vec3<f32>>, // 红色分量变化最快，下标为 r + g * size + b * size * size
+}
+
+impl Lut3D {
+    pub fn from_cube_file(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::parse_cube(&fs::read_to_string(path)?)
+    }
+
+    fn parse_cube(text: &str) -> Result<Self, Box<dyn Error>> {
+        let mut size = 0;
+        let mut domain_min = Vec3::new(0.0, 0.0, 0.0);
+        let mut domain_max = Vec3::new(1.0, 1.0, 1.0);
+        let mut data = Vec::new();
+
+        let parse_triple = |parts: &[&str], line: usize| -> Result<Vec3<f32>, Box<dyn Error>> {
+            if parts.len() != 3 {
+                return Err(format!("第 {} 行应有 3 个数值", line).into());
+            }
+            Ok(Vec3::new(
+                parts[0].parse()?,
+                parts[1].parse()?,
+                parts[2].parse()?,
+            ))
+        };
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts[0] {
+                "TITLE" => {}
+                "LUT_3D_SIZE" => size = parts.get(1).ok_or("LUT_3D_SIZE 缺少数值")?.parse()?,
+                "DOMAIN_MIN" => domain_min = parse_triple(&parts[1..], i + 1)?,
+                "DOMAIN_MAX" => domain_max = parse_triple(&parts[1..], i + 1)?,
+                "LUT_1D_SIZE" => return Err("暂不支持 1D LUT".into()),
+                // 其余关键字（如 LUT_3D_INPUT_RANGE）忽略
+                keyword
+                    if keyword
+                        .chars()
+                        .next()
+                        .is_some_and(|c| c.is_ascii_alphabetic()) => {}
+                _ => data.push(parse_triple(&parts, i + 1)?),
+            }
+        }
+
+        if size < 2 {
+            return Err("缺少有效的 LUT_3D_SIZE".into());
+        }
+        if data.len() != size * size * size {
+            return Err(format!(
+                "LUT 数据数量不符：应为 {}，实际为 {}",
+                size * size * size,
+                data.len()
+            )
+            .into());
+        }
+        Ok(Self {
+            size,
+            domain_min,
+            domain_max,
+            data,
+        })
+    }
+
+    pub fn sample(&self, color: Vec3<f32>, interpolation: LutInterpolation) -> Vec3<f32> {
+        let n = (self.size - 1) as f32;
+        // 映射到格点坐标
+        let to_grid = |c: f32, min: f32, max: f32| ((c - min) / (max - min)).clamp(0.0, 1.0) * n;
+        let r = to_grid(color.x, self.domain_min.x, self.domain_max.x);
+        let g = to_grid(color.y, self.domain_min.y, self.domain_max.y);
+        let b = to_grid(color.z, self.domain_min.z, self.domain_max.z);
+
+        let (r0, g0, b0) = (
+            (r.floor() as usize).min(self.size - 2),
+            (g.floor() as usize).min(self.size - 2),
+            (b.floor() as usize).min(self.size - 2),
+        );
+        let (fr, fg, fb) = (r - r0 as f32, g - g0 as f32, b - b0 as f32);
+        // 立方体顶点，下标的三位分别为 r、g、b 方向是否取上界
+        let c = |dr: usize, dg: usize, db: usize| {
+            self.data[(r0 + dr) + (g0 + dg) * self.size + (b0 + db) * self.size * self.size]
+        };
+
+        match interpolation {
+            LutInterpolation::Trilinear => {
+                let lerp = |a: Vec3<f32>, b: Vec3<f32>, t: f32| a + (b - a) * t;
+                let c00 = lerp(c(0, 0, 0), c(1, 0, 0), fr);
+                let c10 = lerp(c(0, 1, 0), c(1, 1, 0), fr);
+                let c01 = lerp(c(0, 0, 1), c(1, 0, 1), fr);
+                let c11 = lerp(c(0, 1, 1), c(1, 1, 1), fr);
+                lerp(lerp(c00, c10, fg), lerp(c01, c11, fg), fb)
+            }
+            LutInterpolation::Tetrahedral => {
+                let c000 = c(0, 0, 0);
+                let c111 = c(1, 1, 1);
+                if fr > fg {
+                    if fg > fb {
+                        let (c100, c110) = (c(1, 0, 0), c(1, 1, 0));
+                        c000 + (c100 - c000) * fr + (c110 - c100) * fg + (c111 - c110) * fb
+                    } else if fr > fb {
+                        let (c100, c101) = (c(1, 0, 0), c(1, 0, 1));
+                        c000 + (c100 - c000) * fr + (c101 - c100) * fb + (c111 - c101) * fg
+                    } else {
+                        let (c001, c101) = (c(0, 0, 1), c(1, 0, 1));
+                        c000 + (c001 - c000) * fb + (c101 - c001) * fr + (c111 - c101) * fg
+                    }
+                } else if fb > fg {
+                    let (c001, c011) = (c(0, 0, 1), c(0, 1, 1));
+                    c000 + (c001 - c000) * fb + (c011 - c001) * fg + (c111 - c011) * fr
+                } else if fb > fr {
+                    let (c010, c011) = (c(0, 1, 0), c(0, 1, 1));
+                    c000 + (c010 - c000) * fg + (c011 - c010) * fb + (c111 - c011) * fr
+                } else {
+                    let (c010, c110) = (c(0, 1, 0), c(1, 1, 0));
+                    c000 + (c010 - c000) * fg + (c110 - c010) * fr + (c111 - c110) * fb
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::InnerSpace;
+
+    // 单位 LUT：每个格点输出自身坐标，domain 为 [0, scale]
+    fn identity_cube(size: usize, scale: f32) -> String {
+        let mut text = String::from("# 单位 LUT\nTITLE \"identity\"\n");
+        text += &format!("LUT_3D_SIZE {}\n", size);
+        text += &format!("DOMAIN_MIN 0 0 0\nDOMAIN_MAX {0} {0} {0}\n\n", scale);
+        let n = (size - 1) as f32;
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    let v = |i: usize| i as f32 / n * scale;
+                    text += &format!("{} {} {}\n", v(r), v(g), v(b));
+                }
+            }
+        }
+        text
+    }
+
+    #[test]
+    fn parses_header_and_data() {
+        let lut = Lut3D::parse_cube(&identity_cube(2, 2.0)).unwrap();
+        assert_eq!(lut.size, 2);
+        assert_eq!(lut.domain_min, Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!(lut.domain_max, Vec3::new(2.0, 2.0, 2.0));
+        assert_eq!(lut.data.len(), 8);
+        // 红色分量变化最快
+        assert_eq!(lut.data[1], Vec3::new(2.0, 0.0, 0.0));
+        assert_eq!(lut.data[2], Vec3::new(0.0, 2.0, 0.0));
+        assert_eq!(lut.data[4], Vec3::new(0.0, 0.0, 2.0));
+    }
+
+    #[test]
+    fn rejects_bad_files() {
+        assert!(Lut3D::parse_cube("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(Lut3D::parse_cube("0 0 0\n").is_err());
+        assert!(Lut3D::parse_cube("LUT_1D_SIZE 2\n").is_err());
+    }
+
+    #[test]
+    fn identity_lut_keeps_colors() {
+        let colors = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(0.2, 0.5, 0.9),
+            Vec3::new(0.9, 0.5, 0.2),
+            Vec3::new(0.5, 0.9, 0.2),
+            Vec3::new(0.5, 0.2, 0.9),
+            Vec3::new(0.9, 0.2, 0.5),
+            Vec3::new(0.2, 0.9, 0.5),
+            Vec3::new(0.3, 0.3, 0.7),
+        ];
+        for (size, scale) in [(2, 1.0), (5, 1.0), (3, 2.0)] {
+            let lut = Lut3D::parse_cube(&identity_cube(size, scale)).unwrap();
+            for color in colors {
+                let color = color * scale;
+                for interpolation in [LutInterpolation::Tetrahedral, LutInterpolation::Trilinear] {
+                    let out = lut.sample(color, interpolation);
+                    assert!(
+                        (out - color).magnitude() < 1e-5,
+                        "{interpolation:?} size {size}: {color:?} -> {out:?}"
+                    );
+                }
+            }
+        }
+    }
+}
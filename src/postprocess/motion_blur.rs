@@ -0,0 +1,160 @@
+use cgmath::{InnerSpace, Vector2 as Vec2, Vector3 as Vec3};
+use rayon::prelude::*;
+
+use crate::framebuffer::FrameBuffer;
+use crate::postprocess::{PostContext, PostProcess, linear_depth};
+
+// 深度比较的软过渡范围（世界单位）
+const SOFT_Z_EXTENT: f32 = 0.5;
+
+// 运动模糊：沿 velocity 缓冲中的运动矢量做重建滤波（McGuire 2012 的简化版），
+// 运动物体会模糊到静止背景上，静止的前景则不会被背后的运动物体抹花
+pub struct MotionBlur {
+    pub shutter_angle: f32, // 快门角度（度），360 表示快门在整帧时间内都打开
+    pub samples: usize,     // 每个像素沿运动方向的采样数
+    pub max_blur: f32,      // 模糊长度上限（像素）
+}
+
+impl PostProcess for MotionBlur {
+    fn name(&self) -> &str {
+        "运动模糊"
+    }
+
+    fn apply(&self, framebuffer: &mut FrameBuffer, context: &PostContext) {
+        let proj = &context.proj;
+        let width = framebuffer.width;
+        let height = framebuffer.height;
+        let exposure = self.shutter_angle.clamp(0.0, 360.0) / 360.0;
+
+        // 只用快门打开期间的那部分位移，半长用于以像素为中心向两侧模糊
+        let half_velocity: Vec<Vec2<f32>> = framebuffer
+            .velocity
+            .iter()
+            .map(|v| {
+                let v = v * exposure;
+                let length = v.magnitude();
+                if length > self.max_blur {
+                    v * (self.max_blur / length) * 0.5
+                } else {
+                    v * 0.5
+                }
+            })
+            .collect();
+        let depth: Vec<f32> = framebuffer
+            .depth
+            .par_iter()
+            .map(|&d| linear_depth(proj, d))
+            .collect();
+        let colors: Vec<Vec3<f32>> = framebuffer.data.iter().map(|c| c.truncate()).collect();
+
+        let tile_size = (self.max_blur.ceil() as usize).max(8);
+        let neighbor_max = neighbor_max_velocity(&half_velocity, width, height, tile_size);
+        let tiles_x = width.div_ceil(tile_size);
+        let samples = self.samples.max(1);
+
+        let result: Vec<Vec3<f32>> = (0..width * height)
+            .into_par_iter()
+            .map(|idx| {
+                let (x, y) = (idx % width, idx / width);
+                let dominant = neighbor_max[(y / tile_size) * tiles_x + x / tile_size];
+                if dominant.magnitude() < 0.5 {
+                    return colors[idx];
+                }
+
+                let vx = half_velocity[idx].magnitude();
+                let zx = depth[idx];
+                let mut weight_sum = 1.0 / vx.max(0.5);
+                let mut sum = colors[idx] * weight_sum;
+                // 按像素错开采样位置，用噪声代替条带
+                let jitter = ((x * 7 + y * 13) % 4) as f32 / 4.0 - 0.375;
+
+                for i in 0..samples {
+                    let t = ((i as f32 + 0.5 + jitter) / samples as f32) * 2.0 - 1.0;
+                    let offset = dominant * t;
+                    let sx = (x as f32 + offset.x).round();
+                    let sy = (y as f32 + offset.y).round();
+                    if sx < 0.0 || sy < 0.0 || sx >= width as f32 || sy >= height as f32 {
+                        continue;
+                    }
+                    let s = sy as usize * width + sx as usize;
+                    let distance = offset.magnitude();
+                    let vy = half_velocity[s].magnitude();
+                    let zy = depth[s];
+
+                    // 前景样本按自身的模糊范围盖到当前像素上；当前像素在前时按自身的模糊范围透出背景；
+                    // 两者都在运动时按两者共同覆盖的范围混合
+                    let foreground = soft_depth_compare(zy, zx);
+                    let background = soft_depth_compare(zx, zy);
+                    let weight = foreground * cone(distance, vy)
+                        + background * cone(distance, vx)
+                        + cylinder(distance, vy) * cylinder(distance, vx) * 2.0;
+                    sum += colors[s] * weight;
+                    weight_sum += weight;
+                }
+                sum / weight_sum
+            })
+            .collect();
+
+        for (pixel, color) in framebuffer.data.iter_mut().zip(result) {
+            *pixel = color.extend(pixel.w);
+        }
+    }
+}
+
+// 每个分块内最长的运动矢量，再取相邻 3x3 分块中最长的一个
+fn neighbor_max_velocity(
+    velocity: &[Vec2<f32>],
+    width: usize,
+    height: usize,
+    tile_size: usize,
+) -> Vec<Vec2<f32>> {
+    let tiles_x = width.div_ceil(tile_size);
+    let tiles_y = height.div_ceil(tile_size);
+    let longer = |a: Vec2<f32>, b: Vec2<f32>| {
+        if b.magnitude2() > a.magnitude2() {
+            b
+        } else {
+            a
+        }
+    };
+
+    let mut tiles = vec![Vec2::new(0.0, 0.0); tiles_x * tiles_y];
+    for y in 0..height {
+        for x in 0..width {
+            let tile = &mut tiles[(y / tile_size) * tiles_x + x / tile_size];
+            *tile = longer(*tile, velocity[y * width + x]);
+        }
+    }
+    let mut neighbor = vec![Vec2::new(0.0, 0.0); tiles_x * tiles_y];
+    for ty in 0..tiles_y {
+        for tx in 0..tiles_x {
+            let mut max = Vec2::new(0.0, 0.0);
+            for ny in ty.saturating_sub(1)..=(ty + 1).min(tiles_y - 1) {
+                for nx in tx.saturating_sub(1)..=(tx + 1).min(tiles_x - 1) {
+                    max = longer(max, tiles[ny * tiles_x + nx]);
+                }
+            }
+            neighbor[ty * tiles_x + tx] = max;
+        }
+    }
+    neighbor
+}
+
+// a 不比 b 远时为 1，比 b 远出 SOFT_Z_EXTENT 以上时为 0
+fn soft_depth_compare(a: f32, b: f32) -> f32 {
+    (1.0 - (a - b) / SOFT_Z_EXTENT).clamp(0.0, 1.0)
+}
+
+fn cone(distance: f32, velocity: f32) -> f32 {
+    if velocity <= 1e-4 {
+        return 0.0;
+    }
+    (1.0 - distance / velocity).clamp(0.0, 1.0)
+}
+
+fn cylinder(distance: f32, velocity: f32) -> f32 {
+    let edge0 = velocity * 0.95;
+    let edge1 = velocity * 1.05;
+    let t = ((distance - edge0) / (edge1 - edge0).max(1e-4)).clamp(0.0, 1.0);
+    1.0 - t * t * (3.0 - 2.0 * t)
+}
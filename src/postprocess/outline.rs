@@ -0,0 +1,233 @@
+use cgmath::{InnerSpace, Vector3 as Vec3};
+use rayon::prelude::*;
+
+use crate::framebuffer::{BACKGROUND_ID, FrameBuffer};
+use crate::postprocess::{PostContext, PostProcess, linear_depth};
+
+// 背景处的深度值（深度缓冲清空后的值）
+const BACKGROUND_DEPTH: f32 = 1.0;
+
+// 描边检测的数据来源
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlineSource {
+    Color, // 颜色梯度：纹理、明暗交界处也会描边
+    Depth, // 深度梯度：只描物体轮廓，背景像素不参与检测
+}
+
+// Sobel 描边：对指定缓冲做 Sobel 卷积，梯度幅值超过阈值的像素画上 line_width 宽的线
+pub struct SobelOutline {
+    pub source: OutlineSource,
+    pub threshold: f32,
+    pub line_width: usize,
+    pub color: Vec3<f32>,
+}
+
+impl PostProcess for SobelOutline {
+    fn name(&self) -> &str {
+        match self.source {
+            OutlineSource::Color => "颜色描边",
+            OutlineSource::Depth => "深度描边",
+        }
+    }
+
+    fn apply(&self, framebuffer: &mut FrameBuffer, _context: &PostContext) {
+        let width = framebuffer.width;
+        let height = framebuffer.height;
+        if width < 3 || height < 3 {
+            return;
+        }
+
+        // 把要检测的缓冲统一整理成每像素若干通道
+        let channels: Vec<Vec3<f32>> = match self.source {
+            OutlineSource::Color => framebuffer.data.iter().map(|c| c.truncate()).collect(),
+            OutlineSource::Depth => framebuffer
+                .depth
+                .iter()
+                .map(|&d| Vec3::new(d, d, d))
+                .collect(),
+        };
+
+        let mut outline_pixels = Vec::new();
+        // 遍历计算梯度（避开边界像素）
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                if self.source == OutlineSource::Depth
+                    && (framebuffer.depth[y * width + x] - BACKGROUND_DEPTH).abs() < 1e-6
+                {
+                    continue; // 背景像素不参与边缘检测
+                }
+                if sobel_magnitude(&channels, width, x, y) > self.threshold {
+                    outline_pixels.push((x, y));
+                }
+            }
+        }
+
+        // 绘制指定线宽的轮廓
+        for &(x, y) in &outline_pixels {
+            for draw_y in y..(y + self.line_width).min(height) {
+                for draw_x in x..(x + self.line_width).min(width) {
+                    let pixel = &mut framebuffer.data[draw_y * width + draw_x];
+                    *pixel = self.color.extend(pixel.w);
+                }
+            }
+        }
+    }
+}
+
+// 一类边缘的检测阈值与线条颜色
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeLayer {
+    pub threshold: f32,
+    pub color: Vec3<f32>,
+}
+
+// 线稿描边：综合深度、法线夹角与物体编号三种边缘，各自使用独立的阈值与线条颜色，
+// 能描出模型内部的折痕以及同一深度上相互接触的物体之间的分界。
+// 线条只画在一对相邻像素中较近的一侧，因此轮廓线落在物体上而不是背景上
+pub struct EdgeOutline {
+    pub depth: Option<EdgeLayer>,     // 阈值为相邻像素线性深度的相对差
+    pub normal: Option<EdgeLayer>,    // 阈值为相邻像素法线的夹角（度）
+    pub object_id: Option<EdgeLayer>, // 物体编号不同即为边缘，不使用阈值
+    pub line_width: usize,
+}
+
+impl PostProcess for EdgeOutline {
+    fn name(&self) -> &str {
+        "线稿描边"
+    }
+
+    fn apply(&self, framebuffer: &mut FrameBuffer, context: &PostContext) {
+        let width = framebuffer.width;
+        let height = framebuffer.height;
+        let depth: Vec<f32> = framebuffer
+            .depth
+            .par_iter()
+            .map(|&d| linear_depth(&context.proj, d))
+            .collect();
+        let normal: Vec<Vec3<f32>> = framebuffer
+            .normal
+            .iter()
+            .map(|n| {
+                if n.magnitude2() > 0.0 {
+                    n.normalize()
+                } else {
+                    *n
+                }
+            })
+            .collect();
+        let buffers = EdgeBuffers {
+            width,
+            height,
+            depth: &depth,
+            normal: &normal,
+            object_id: &framebuffer.object_id,
+        };
+        let normal_cos = self.normal.map(|layer| layer.threshold.to_radians().cos());
+
+        let edges: Vec<Option<Vec3<f32>>> = (0..width * height)
+            .into_par_iter()
+            .map(|idx| self.edge_color(&buffers, idx, normal_cos))
+            .collect();
+
+        for (idx, edge) in edges.into_iter().enumerate() {
+            let Some(color) = edge else {
+                continue;
+            };
+            let (x, y) = (idx % width, idx / width);
+            for draw_y in y..(y + self.line_width).min(height) {
+                for draw_x in x..(x + self.line_width).min(width) {
+                    let pixel = &mut framebuffer.data[draw_y * width + draw_x];
+                    *pixel = color.extend(pixel.w);
+                }
+            }
+        }
+    }
+}
+
+// 边缘检测用到的几何缓冲
+struct EdgeBuffers<'a> {
+    width: usize,
+    height: usize,
+    depth: &'a [f32],
+    normal: &'a [Vec3<f32>],
+    object_id: &'a [u32],
+}
+
+impl EdgeOutline {
+    // 当前像素是否在边缘上；同时满足多种边缘时，物体编号优先，其次深度，最后法线
+    fn edge_color(
+        &self,
+        buffers: &EdgeBuffers,
+        idx: usize,
+        normal_cos: Option<f32>,
+    ) -> Option<Vec3<f32>> {
+        if buffers.object_id[idx] == BACKGROUND_ID {
+            return None;
+        }
+        let (x, y) = (idx % buffers.width, idx / buffers.width);
+        let mut neighbors = [None; 4];
+        if x > 0 {
+            neighbors[0] = Some(idx - 1);
+        }
+        if x + 1 < buffers.width {
+            neighbors[1] = Some(idx + 1);
+        }
+        if y > 0 {
+            neighbors[2] = Some(idx - buffers.width);
+        }
+        if y + 1 < buffers.height {
+            neighbors[3] = Some(idx + buffers.width);
+        }
+
+        let z = buffers.depth[idx];
+        // 只有当前像素较近时才算边缘，避免线条在两侧各画一次
+        let nearer = |n: usize| z < buffers.depth[n] || (z == buffers.depth[n] && idx < n);
+        let mut is_id_edge = false;
+        let mut is_depth_edge = false;
+        let mut is_normal_edge = false;
+        for n in neighbors.into_iter().flatten() {
+            if !nearer(n) {
+                continue;
+            }
+            if buffers.object_id[n] != buffers.object_id[idx] {
+                is_id_edge = true;
+            }
+            if let Some(layer) = self.depth
+                && (buffers.depth[n] - z) / z.max(1e-4) > layer.threshold
+            {
+                is_depth_edge = true;
+            }
+            if let Some(cos) = normal_cos
+                && buffers.object_id[n] != BACKGROUND_ID
+                && buffers.normal[idx].dot(buffers.normal[n]) < cos
+            {
+                is_normal_edge = true;
+            }
+        }
+
+        match (self.object_id, self.depth, self.normal) {
+            (Some(layer), _, _) if is_id_edge => Some(layer.color),
+            (_, Some(layer), _) if is_depth_edge => Some(layer.color),
+            (_, _, Some(layer)) if is_normal_edge => Some(layer.color),
+            _ => None,
+        }
+    }
+}
+
+// 3x3 Sobel 梯度幅值（简化为 |Gx| + |Gy|），多通道时取最大的一个
+fn sobel_magnitude(channels: &[Vec3<f32>], width: usize, x: usize, y: usize) -> f32 {
+    const SOBEL_X: [[f32; 3]; 3] = [[-1.0, 0.0, 1.0], [-2.0, 0.0, 2.0], [-1.0, 0.0, 1.0]];
+    const SOBEL_Y: [[f32; 3]; 3] = [[-1.0, -2.0, -1.0], [0.0, 0.0, 0.0], [1.0, 2.0, 1.0]];
+
+    let mut gx = Vec3::new(0.0, 0.0, 0.0);
+    let mut gy = Vec3::new(0.0, 0.0, 0.0);
+    for ky in 0..3 {
+        for kx in 0..3 {
+            let sample = channels[(y + ky - 1) * width + x + kx - 1];
+            gx += sample * SOBEL_X[ky][kx];
+            gy += sample * SOBEL_Y[ky][kx];
+        }
+    }
+    let magnitude = gx.map(f32::abs) + gy.map(f32::abs);
+    magnitude.x.max(magnitude.y).max(magnitude.z)
+}
@@ -0,0 +1,294 @@
+use cgmath::Vector3 as Vec3;
+use rayon::prelude::*;
+use std::error::Error;
+use std::path::Path;
+
+// 调色板：颜色按 sRGB 编码保存，在 sRGB 空间中找最接近的颜色，输出时再转回线性空间
+pub struct Palette {
+    pub colors: Vec<Vec3<f32>>,
+}
+
+impl Palette {
+    pub fn nearest(&self, srgb: Vec3<f32>) -> Vec3<f32> {
+        self.colors
+            .get(self.nearest_index(srgb))
+            .copied()
+            .unwrap_or(srgb)
+    }
+
+    fn nearest_index(&self, srgb: Vec3<f32>) -> usize {
+        let distance = |c: &Vec3<f32>| {
+            let d = c - srgb;
+            d.x * d.x + d.y * d.y + d.z * d.z
+        };
+        self.colors
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+            .map(|(index, _)| index)
+            .unwrap_or(0)
+    }
+
+    // 从调色板文件读取：.hex 每行一个十六进制颜色（可带 #），.gpl 为 GIMP 调色板，
+    // 其它扩展名按图片读取，图片中出现的每种颜色按扫描顺序各取一次
+    pub fn from_file(path: &Path) -> Result<Palette, Box<dyn Error>> {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let colors = match extension.as_str() {
+            "hex" => parse_hex(&std::fs::read_to_string(path)?)?,
+            "gpl" => parse_gpl(&std::fs::read_to_string(path)?)?,
+            _ => {
+                let image = image::open(path)?.to_rgb8();
+                let mut colors: Vec<[u8; 3]> = Vec::new();
+                for pixel in image.pixels() {
+                    if !colors.contains(&pixel.0) {
+                        colors.push(pixel.0);
+                    }
+                }
+                colors.into_iter().map(rgb8).collect()
+            }
+        };
+        if colors.is_empty() {
+            return Err(format!("调色板文件 {} 中没有颜色", path.display()).into());
+        }
+        Ok(Palette { colors })
+    }
+
+    // 中位切分：不断把颜色范围最大的盒子沿最长的通道从中位数处一分为二，
+    // 直到得到 count 个盒子，每个盒子的平均颜色作为调色板中的一种颜色
+    pub fn median_cut(colors: &[Vec3<f32>], count: usize) -> Palette {
+        if colors.is_empty() || count == 0 {
+            return Palette { colors: Vec::new() };
+        }
+        let mut boxes = vec![colors.to_vec()];
+        while boxes.len() < count {
+            let Some((index, axis, range)) = boxes
+                .iter()
+                .enumerate()
+                .map(|(index, b)| {
+                    let (axis, range) = widest_axis(b);
+                    (index, axis, range)
+                })
+                .max_by(|a, b| a.2.total_cmp(&b.2))
+            else {
+                break;
+            };
+            if range <= 0.0 {
+                break; // 所有盒子都只剩一种颜色
+            }
+            let mut lower = boxes.swap_remove(index);
+            lower.sort_unstable_by(|a, b| a[axis].total_cmp(&b[axis]));
+            let upper = lower.split_off(lower.len() / 2);
+            boxes.push(lower);
+            boxes.push(upper);
+        }
+        Palette {
+            colors: boxes.iter().map(|b| mean(b)).collect(),
+        }
+    }
+
+    // k-means：以中位切分的结果为初始中心，反复把颜色分配给最近的中心并把中心移到所属颜色的平均值。
+    // 没有分到颜色的中心保持不动
+    pub fn kmeans(colors: &[Vec3<f32>], count: usize, iterations: usize) -> Palette {
+        let mut palette = Palette::median_cut(colors, count);
+        for _ in 0..iterations {
+            let k = palette.colors.len();
+            let (sums, counts) = colors
+                .par_iter()
+                .fold(
+                    || (vec![Vec3::new(0.0, 0.0, 0.0); k], vec![0usize; k]),
+                    |(mut sums, mut counts), &c| {
+                        let index = palette.nearest_index(c);
+                        sums[index] += c;
+                        counts[index] += 1;
+                        (sums, counts)
+                    },
+                )
+                .reduce(
+                    || (vec![Vec3::new(0.0, 0.0, 0.0); k], vec![0usize; k]),
+                    |(mut sums, mut counts), (other_sums, other_counts)| {
+                        for i in 0..k {
+                            sums[i] += other_sums[i];
+                            counts[i] += other_counts[i];
+                        }
+                        (sums, counts)
+                    },
+                );
+            let mut shift = 0.0f32;
+            for i in 0..k {
+                if counts[i] > 0 {
+                    let center = sums[i] / counts[i] as f32;
+                    let d = center - palette.colors[i];
+                    shift = shift.max(d.x.abs().max(d.y.abs()).max(d.z.abs()));
+                    palette.colors[i] = center;
+                }
+            }
+            if shift < 1e-4 {
+                break; // 已收敛
+            }
+        }
+        palette
+    }
+}
+
+// 颜色范围最大的通道及其范围
+fn widest_axis(colors: &[Vec3<f32>]) -> (usize, f32) {
+    let mut min = Vec3::new(f32::MAX, f32::MAX, f32::MAX);
+    let mut max = Vec3::new(f32::MIN, f32::MIN, f32::MIN);
+    for c in colors {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(c[axis]);
+            max[axis] = max[axis].max(c[axis]);
+        }
+    }
+    (0..3)
+        .map(|axis| (axis, max[axis] - min[axis]))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((0, 0.0))
+}
+
+fn mean(colors: &[Vec3<f32>]) -> Vec3<f32> {
+    let sum = colors
+        .iter()
+        .fold(Vec3::new(0.0, 0.0, 0.0), |sum, &c| sum + c);
+    sum / colors.len().max(1) as f32
+}
+
+fn rgb8(c: [u8; 3]) -> Vec3<f32> {
+    Vec3::new(c[0] as f32, c[1] as f32, c[2] as f32) / 255.0
+}
+
+// 每行一个 RRGGBB，空行与 ; 开头的注释行忽略
+fn parse_hex(text: &str) -> Result<Vec<Vec3<f32>>, Box<dyn Error>> {
+    let mut colors = Vec::new();
+    for line in text.lines() {
+        let line = line.trim().trim_start_matches('#');
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+        let value = u32::from_str_radix(line, 16)
+            .ok()
+            .filter(|_| line.len() == 6)
+            .ok_or_else(|| format!("无法解析的十六进制颜色：{}", line))?;
+        colors.push(rgb8([(value >> 16) as u8, (value >> 8) as u8, value as u8]));
+    }
+    Ok(colors)
+}
+
+// GIMP 调色板：首行为 "GIMP Palette"，之后是 Name: / Columns: 等头部与 # 注释，
+// 颜色行以三个 0 ~ 255 的整数开头，后面可跟颜色名
+fn parse_gpl(text: &str) -> Result<Vec<Vec3<f32>>, Box<dyn Error>> {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim) != Some("GIMP Palette") {
+        return Err("GIMP 调色板文件应以 \"GIMP Palette\" 开头".into());
+    }
+    let mut colors = Vec::new();
+    for line in lines {
+        let line = line.trim();
+        if !line.starts_with(|c: char| c.is_ascii_digit()) {
+            continue; // 空行、注释与头部
+        }
+        let channels: Vec<u8> = line
+            .split_whitespace()
+            .take(3)
+            .map(|v| v.parse::<u8>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("无法解析的 GIMP 调色板颜色：{}", line))?;
+        if channels.len() < 3 {
+            return Err(format!("无法解析的 GIMP 调色板颜色：{}", line).into());
+        }
+        colors.push(rgb8([channels[0], channels[1], channels[2]]));
+    }
+    Ok(colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::InnerSpace;
+
+    fn close(a: Vec3<f32>, b: Vec3<f32>) -> bool {
+        (a - b).magnitude() < 1e-4
+    }
+
+    // 按红、绿、蓝依次排序，便于与期望结果逐个比较
+    fn sorted(mut colors: Vec<Vec3<f32>>) -> Vec<Vec3<f32>> {
+        colors.sort_by(|a, b| {
+            a.x.total_cmp(&b.x)
+                .then(a.y.total_cmp(&b.y))
+                .then(a.z.total_cmp(&b.z))
+        });
+        colors
+    }
+
+    #[test]
+    fn hex_skips_comments_and_blank_lines() {
+        let colors = parse_hex("; 注释\n#FF0000\n\n00ff80\n  #000000  \n").unwrap();
+        assert_eq!(colors.len(), 3);
+        assert!(close(colors[0], Vec3::new(1.0, 0.0, 0.0)));
+        assert!(close(colors[1], Vec3::new(0.0, 1.0, 128.0 / 255.0)));
+        assert!(close(colors[2], Vec3::new(0.0, 0.0, 0.0)));
+
+        assert!(parse_hex("#FFF\n").is_err());
+        assert!(parse_hex("GGGGGG\n").is_err());
+    }
+
+    #[test]
+    fn gpl_reads_colors_after_the_header() {
+        let text = "GIMP Palette\nName: test\nColumns: 2\n# 注释\n255   0   0 Red\n  0 128 255\n";
+        let colors = parse_gpl(text).unwrap();
+        assert_eq!(colors.len(), 2);
+        assert!(close(colors[0], Vec3::new(1.0, 0.0, 0.0)));
+        assert!(close(colors[1], Vec3::new(0.0, 128.0 / 255.0, 1.0)));
+
+        assert!(parse_gpl("255 0 0\n").is_err());
+        assert!(parse_gpl("GIMP Palette\n255 0\n").is_err());
+        assert!(parse_gpl("GIMP Palette\n300 0 0\n").is_err());
+    }
+
+    // 两簇颜色：暗红附近与亮蓝附近
+    fn two_clusters() -> Vec<Vec3<f32>> {
+        vec![
+            Vec3::new(0.1, 0.0, 0.0),
+            Vec3::new(0.3, 0.0, 0.0),
+            Vec3::new(0.2, 0.1, 0.0),
+            Vec3::new(0.0, 0.0, 0.8),
+            Vec3::new(0.0, 0.2, 1.0),
+        ]
+    }
+
+    #[test]
+    fn median_cut_splits_along_the_widest_channel() {
+        let palette = Palette::median_cut(&two_clusters(), 2);
+        let colors = sorted(palette.colors);
+        assert_eq!(colors.len(), 2);
+        // 蓝色通道范围最大，按蓝色排序后从中位数处分开：较小的一半只有两种暗红，
+        // 第三种暗红被分到了蓝色一侧（k-means 会修正这一点）
+        assert!(close(colors[0], Vec3::new(0.2, 0.3, 1.8) / 3.0), "{colors:?}");
+        assert!(close(colors[1], Vec3::new(0.2, 0.0, 0.0)), "{colors:?}");
+
+        // 请求的颜色数多于不同颜色数时停止切分
+        let single = Palette::median_cut(&[Vec3::new(0.5, 0.5, 0.5); 4], 3);
+        assert_eq!(single.colors.len(), 1);
+        assert!(Palette::median_cut(&[], 4).colors.is_empty());
+    }
+
+    #[test]
+    fn kmeans_moves_centers_to_cluster_means() {
+        let palette = Palette::kmeans(&two_clusters(), 2, 10);
+        let colors = sorted(palette.colors);
+        assert_eq!(colors.len(), 2);
+        assert!(close(colors[0], Vec3::new(0.0, 0.1, 0.9)), "{colors:?}");
+        assert!(close(colors[1], Vec3::new(0.2, 1.0 / 30.0, 0.0)), "{colors:?}");
+        assert!(close(palette_nearest(&colors, Vec3::new(0.25, 0.0, 0.0)), colors[1]));
+    }
+
+    fn palette_nearest(colors: &[Vec3<f32>], srgb: Vec3<f32>) -> Vec3<f32> {
+        Palette {
+            colors: colors.to_vec(),
+        }
+        .nearest(srgb)
+    }
+}
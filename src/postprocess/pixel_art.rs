@@ -0,0 +1,153 @@
+use cgmath::Vector3 as Vec3;
+use rayon::prelude::*;
+
+use crate::color::{linear_to_srgb, srgb_to_linear};
+use crate::framebuffer::{BACKGROUND_ID, FrameBuffer};
+use crate::postprocess::dither::{bayer_matrix, block_average};
+use crate::postprocess::palette::Palette;
+use crate::postprocess::{PostContext, PostProcess, linear_depth};
+
+// 有序抖动使用的 Bayer 矩阵边长
+const DITHER_MATRIX_SIZE: usize = 4;
+
+// 把画面缩小为低分辨率像素的方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Downsample {
+    Nearest, // 取每块中心的像素，与直接以低分辨率渲染的结果相同，边缘不会出现过渡色
+    Average, // 块内平均，保留更多细节但轮廓处会出现混合色
+}
+
+// 调色板的来源
+pub enum PaletteSource {
+    Fixed(Palette),                              // 固定调色板
+    MedianCut(usize),                            // 每帧由低分辨率画面中位切分出指定数量的颜色
+    KMeans { colors: usize, iterations: usize }, // 每帧由低分辨率画面做 k-means 聚类
+}
+
+// 像素画：把画面缩小为 pixel_size 大小的低分辨率像素，量化到调色板（可加有序抖动），
+// 在物体轮廓内侧画一圈像素描边，再以最近邻方式放大回原尺寸。
+// 由画面生成的调色板每帧重新计算，动画中颜色可能轻微跳变，需要稳定的颜色时使用固定调色板
+pub struct PixelArt {
+    pub pixel_size: usize,
+    pub downsample: Downsample,
+    pub palette: PaletteSource,
+    pub dither: f32,                // 有序抖动的幅度（sRGB 单位），0 为不抖动
+    pub outline: Option<Vec3<f32>>, // 描边颜色（sRGB 编码），None 为不描边
+    pub outline_depth: f32,         // 同一物体内线性深度的相对差超过该值时也视为轮廓
+}
+
+impl PostProcess for PixelArt {
+    fn name(&self) -> &str {
+        "像素画"
+    }
+
+    fn apply(&self, framebuffer: &mut FrameBuffer, context: &PostContext) {
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        let size = self.pixel_size.max(1);
+        let blocks_x = width.div_ceil(size);
+        let blocks_y = height.div_ceil(size);
+        // 每块中心像素的下标，低分辨率画面的几何信息都取自这里
+        let centers: Vec<usize> = (0..blocks_x * blocks_y)
+            .map(|idx| {
+                let x = ((idx % blocks_x) * size + size / 2).min(width - 1);
+                let y = ((idx / blocks_x) * size + size / 2).min(height - 1);
+                y * width + x
+            })
+            .collect();
+
+        let colors: Vec<Vec3<f32>> = match self.downsample {
+            Downsample::Nearest => centers
+                .iter()
+                .map(|&i| {
+                    framebuffer.data[i]
+                        .truncate()
+                        .map(|v| linear_to_srgb(v).clamp(0.0, 1.0))
+                })
+                .collect(),
+            Downsample::Average => block_average(framebuffer, size, blocks_x, blocks_y),
+        };
+
+        let generated;
+        let palette = match &self.palette {
+            PaletteSource::Fixed(palette) => palette,
+            PaletteSource::MedianCut(count) => {
+                generated = Palette::median_cut(&colors, *count);
+                &generated
+            }
+            PaletteSource::KMeans {
+                colors: count,
+                iterations,
+            } => {
+                generated = Palette::kmeans(&colors, *count, *iterations);
+                &generated
+            }
+        };
+        if palette.colors.is_empty() {
+            return;
+        }
+
+        let matrix = bayer_matrix(DITHER_MATRIX_SIZE);
+        let mut pixels: Vec<Vec3<f32>> = colors
+            .par_iter()
+            .enumerate()
+            .map(|(idx, &c)| {
+                let (x, y) = (idx % blocks_x, idx / blocks_x);
+                let threshold =
+                    matrix[(y % DITHER_MATRIX_SIZE) * DITHER_MATRIX_SIZE + x % DITHER_MATRIX_SIZE];
+                let offset = self.dither * (threshold - 0.5);
+                palette.nearest(c + Vec3::new(offset, offset, offset))
+            })
+            .collect();
+
+        if let Some(outline) = self.outline {
+            let depth: Vec<f32> = centers
+                .iter()
+                .map(|&i| linear_depth(&context.proj, framebuffer.depth[i]))
+                .collect();
+            let ids: Vec<u32> = centers.iter().map(|&i| framebuffer.object_id[i]).collect();
+            for (idx, pixel) in pixels.iter_mut().enumerate() {
+                if self.is_outline(&depth, &ids, blocks_x, blocks_y, idx) {
+                    *pixel = outline;
+                }
+            }
+        }
+
+        framebuffer
+            .data
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(idx, pixel)| {
+                let (x, y) = (idx % width, idx / width);
+                let c = pixels[(y / size) * blocks_x + x / size];
+                *pixel = c.map(srgb_to_linear).extend(pixel.w);
+            });
+    }
+}
+
+impl PixelArt {
+    // 上下左右有更远的像素属于另一个物体（或背景），或与它的深度相差过大时，
+    // 该像素位于轮廓内侧。描边只画在较近的一侧，因此物体的外形不会被描边加粗
+    fn is_outline(
+        &self,
+        depth: &[f32],
+        ids: &[u32],
+        width: usize,
+        height: usize,
+        idx: usize,
+    ) -> bool {
+        if ids[idx] == BACKGROUND_ID {
+            return false;
+        }
+        let (x, y) = ((idx % width) as isize, (idx / width) as isize);
+        let z = depth[idx];
+        [(-1, 0), (1, 0), (0, -1), (0, 1)].iter().any(|&(dx, dy)| {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                return false;
+            }
+            let n = ny as usize * width + nx as usize;
+            let farther = (depth[n] - z) / z.max(1e-4);
+            farther > 0.0 && (ids[n] != ids[idx] || farther > self.outline_depth)
+        })
+    }
+}
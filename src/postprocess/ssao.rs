@@ -0,0 +1,238 @@
+use cgmath::{InnerSpace, Matrix4 as Mat4, SquareMatrix, Vector3 as Vec3, Vector4 as Vec4};
+use rayon::prelude::*;
+
+use crate::framebuffer::FrameBuffer;
+use crate::noise::hash;
+
+// 旋转噪声平铺的边长（像素），模糊半径应不小于它的一半以消除噪声图案
+const NOISE_SIZE: usize = 4;
+
+// 屏幕空间环境光遮蔽：由深度缓冲与投影矩阵重建观察空间位置，
+// 在法线方向的半球内采样，统计被场景遮挡的比例，结果写入 framebuffer.ambient_occlusion
+// 需要先用深度预渲染填充 depth 与 normal
+pub struct Ssao {
+    pub radius: f32,        // 采样半球半径（世界单位）
+    pub samples: usize,     // 每个像素的采样数
+    pub bias: f32,          // 深度比较偏移，防止平面自遮挡
+    pub intensity: f32,     // 遮蔽强度，作为指数作用在可见度上
+    pub blur_radius: usize, // 双边模糊半径（像素）
+}
+
+impl Ssao {
+    pub fn apply(&self, framebuffer: &mut FrameBuffer, view: &Mat4<f32>, proj: &Mat4<f32>) {
+        let width = framebuffer.width;
+        let height = framebuffer.height;
+        let inv_proj = proj.invert().unwrap();
+        let kernel = self.kernel();
+
+        // 观察空间位置与法线
+        let positions: Vec<Option<Vec3<f32>>> = (0..width * height)
+            .into_par_iter()
+            .map(|idx| {
+                let depth = framebuffer.depth[idx];
+                if depth >= 1.0 {
+                    return None;
+                }
+                Some(view_position(
+                    &inv_proj,
+                    (idx % width) as f32 + 0.5,
+                    (idx / width) as f32 + 0.5,
+                    depth,
+                    width,
+                    height,
+                ))
+            })
+            .collect();
+        let normals: Vec<Vec3<f32>> = framebuffer
+            .normal
+            .par_iter()
+            .map(|n| (view * n.extend(0.0)).truncate())
+            .collect();
+
+        let geometry = ViewGeometry {
+            width,
+            height,
+            positions,
+            normals,
+        };
+
+        let mut occlusion = vec![1.0; width * height];
+        occlusion
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (x, out) in row.iter_mut().enumerate() {
+                    *out = self.occlusion_at(&geometry, x, y, &kernel, proj);
+                }
+            });
+
+        // 先横向再纵向的双边模糊，跨越深度或法线突变处时降低权重，避免遮蔽渗到前景物体上
+        let blurred = self.bilateral_blur(&occlusion, &geometry, (1, 0));
+        let blurred = self.bilateral_blur(&blurred, &geometry, (0, 1));
+        framebuffer.ambient_occlusion = blurred;
+    }
+
+    // 生成位于 +Z 半球内的采样核，越靠后的样本离中心越远
+    fn kernel(&self) -> Vec<Vec3<f32>> {
+        let count = self.samples.max(1);
+        (0..count)
+            .map(|i| {
+                let dir = Vec3::new(
+                    hash(i as u32 * 3) * 2.0 - 1.0,
+                    hash(i as u32 * 3 + 1) * 2.0 - 1.0,
+                    hash(i as u32 * 3 + 2).max(0.05),
+                )
+                .normalize();
+                let t = i as f32 / count as f32;
+                let scale = 0.1 + 0.9 * t * t;
+                dir * hash(i as u32 + 7919).max(0.1) * scale
+            })
+            .collect()
+    }
+
+    fn occlusion_at(
+        &self,
+        geometry: &ViewGeometry,
+        x: usize,
+        y: usize,
+        kernel: &[Vec3<f32>],
+        proj: &Mat4<f32>,
+    ) -> f32 {
+        let (width, height) = (geometry.width, geometry.height);
+        let idx = y * width + x;
+        let (Some(position), normal) = (geometry.positions[idx], geometry.normals[idx]) else {
+            return 1.0;
+        };
+        if normal.magnitude2() < 1e-8 {
+            return 1.0;
+        }
+        let normal = normal.normalize();
+        let noise = noise_vector(x, y);
+        // 用随机向量构造切线空间，使相邻像素的采样核朝向不同
+        let tangent = noise - normal * noise.dot(normal);
+        if tangent.magnitude2() < 1e-8 {
+            return 1.0;
+        }
+        let tangent = tangent.normalize();
+        let bitangent = normal.cross(tangent);
+
+        let mut occluded = 0.0;
+        for sample in kernel {
+            let offset = tangent * sample.x + bitangent * sample.y + normal * sample.z;
+            let sample_pos = position + offset * self.radius;
+
+            // 投影回屏幕，读取该处场景表面的深度
+            let clip = proj * Vec4::new(sample_pos.x, sample_pos.y, sample_pos.z, 1.0);
+            if clip.w <= 0.0 {
+                continue;
+            }
+            let sx = (clip.x / clip.w + 1.0) * 0.5 * width as f32;
+            let sy = (1.0 - clip.y / clip.w) * 0.5 * height as f32;
+            if sx < 0.0 || sy < 0.0 || sx >= width as f32 || sy >= height as f32 {
+                continue;
+            }
+            let Some(scene_pos) = geometry.positions[sy as usize * width + sx as usize] else {
+                continue;
+            };
+
+            // 观察空间朝 -Z 看，z 越大越靠近相机
+            if scene_pos.z >= sample_pos.z + self.bias {
+                // 距离过远的遮挡物（如背景中的另一物体）贡献逐渐衰减
+                let range = self.radius / (position.z - scene_pos.z).abs().max(1e-5);
+                occluded += smoothstep(range);
+            }
+        }
+
+        (1.0 - occluded / kernel.len() as f32)
+            .clamp(0.0, 1.0)
+            .powf(self.intensity)
+    }
+
+    fn bilateral_blur(
+        &self,
+        source: &[f32],
+        geometry: &ViewGeometry,
+        (step_x, step_y): (i32, i32),
+    ) -> Vec<f32> {
+        let (width, height) = (geometry.width, geometry.height);
+        let (positions, normals) = (&geometry.positions, &geometry.normals);
+        let radius = self.blur_radius as i32;
+        let sigma = (radius as f32 * 0.5).max(0.5);
+        let mut result = source.to_vec();
+        result
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (x, out) in row.iter_mut().enumerate() {
+                    let idx = y * width + x;
+                    let Some(center) = positions[idx] else {
+                        continue;
+                    };
+                    let center_normal = normals[idx];
+                    let mut sum = 0.0;
+                    let mut weight_sum = 0.0;
+                    for i in -radius..=radius {
+                        let nx = x as i32 + i * step_x;
+                        let ny = y as i32 + i * step_y;
+                        if nx < 0 || ny < 0 || nx >= width as i32 || ny >= height as i32 {
+                            continue;
+                        }
+                        let n_idx = ny as usize * width + nx as usize;
+                        let Some(neighbor) = positions[n_idx] else {
+                            continue;
+                        };
+                        let spatial = (-(i * i) as f32 / (2.0 * sigma * sigma)).exp();
+                        let depth_diff = (neighbor.z - center.z).abs() / center.z.abs().max(1e-3);
+                        let range = (-depth_diff * 50.0).exp();
+                        let facing = center_normal.dot(normals[n_idx]).max(0.0).powi(8);
+                        let weight = spatial * range * facing;
+                        sum += source[n_idx] * weight;
+                        weight_sum += weight;
+                    }
+                    if weight_sum > 1e-6 {
+                        *out = sum / weight_sum;
+                    }
+                }
+            });
+        result
+    }
+}
+
+// 由深度与法线缓冲重建的观察空间几何信息，背景处位置为 None
+struct ViewGeometry {
+    width: usize,
+    height: usize,
+    positions: Vec<Option<Vec3<f32>>>,
+    normals: Vec<Vec3<f32>>,
+}
+
+// 把屏幕坐标与深度 (0 ~ 1) 反投影为观察空间位置
+fn view_position(
+    inv_proj: &Mat4<f32>,
+    sx: f32,
+    sy: f32,
+    depth: f32,
+    width: usize,
+    height: usize,
+) -> Vec3<f32> {
+    let ndc = Vec4::new(
+        sx / width as f32 * 2.0 - 1.0,
+        1.0 - sy / height as f32 * 2.0,
+        depth * 2.0 - 1.0,
+        1.0,
+    );
+    let view = inv_proj * ndc;
+    view.truncate() / view.w
+}
+
+// 按像素位置在 NOISE_SIZE x NOISE_SIZE 的平铺中取一个 XY 平面内的随机方向
+fn noise_vector(x: usize, y: usize) -> Vec3<f32> {
+    let i = ((y % NOISE_SIZE) * NOISE_SIZE + x % NOISE_SIZE) as u32;
+    let angle = hash(i + 101) * std::f32::consts::TAU;
+    Vec3::new(angle.cos(), angle.sin(), 0.0)
+}
+
+fn smoothstep(x: f32) -> f32 {
+    let t = x.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
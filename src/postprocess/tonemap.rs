@@ -0,0 +1,192 @@
+use cgmath::Vector3 as Vec3;
+
+use crate::color::luminance;
+use crate::framebuffer::FrameBuffer;
+use crate::postprocess::{PostContext, PostProcess};
+
+// 色调映射算子
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToneMapOperator {
+    Clamp,    // 直接截断到 [0, 1]（旧行为）
+    Reinhard, // c / (1 + c)
+    Aces,     // ACES 电影曲线（Stephen Hill 的 RRT+ODT 拟合）
+    Agx,      // AgX 风格曲线，高光去饱和更自然
+}
+
+// 曝光方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Exposure {
+    // 手动曝光，单位为 EV（0 表示不变，+1 表示亮一倍）
+    Manual(f32),
+    // 根据亮度直方图自动曝光：去掉最暗 low 和最亮 1 - high 比例的像素后求平均亮度，
+    // 再把它映射到 18% 中灰；compensation 为额外的曝光补偿 (EV)
+    Auto { low: f32, high: f32, compensation: f32 },
+}
+
+// 直方图覆盖的对数亮度范围 (EV)
+const HISTOGRAM_MIN_EV: f32 = -12.0;
+const HISTOGRAM_MAX_EV: f32 = 8.0;
+const HISTOGRAM_BINS: usize = 128;
+const MIDDLE_GRAY: f32 = 0.18;
+
+// 把 HDR 的 FrameBuffer 解析为 [0, 1] 的显示颜色（仍是线性空间，保存时再编码 sRGB）
+pub struct ToneMapping {
+    pub operator: ToneMapOperator,
+    pub exposure: Exposure,
+}
+
+impl PostProcess for ToneMapping {
+    fn name(&self) -> &str {
+        "色调映射"
+    }
+
+    fn apply(&self, framebuffer: &mut FrameBuffer, _context: &PostContext) {
+        let scale = self.exposure_scale(framebuffer);
+        for pixel in framebuffer.data.iter_mut() {
+            let color = self.map(pixel.truncate() * scale);
+            *pixel = color.extend(pixel.w);
+        }
+    }
+}
+
+impl ToneMapping {
+    // 计算曝光系数
+    fn exposure_scale(&self, framebuffer: &FrameBuffer) -> f32 {
+        match self.exposure {
+            Exposure::Manual(ev) => ev.exp2(),
+            Exposure::Auto {
+                low,
+                high,
+                compensation,
+            } => {
+                let average = histogram_average_luminance(framebuffer, low, high);
+                MIDDLE_GRAY / average.max(1e-6) * compensation.exp2()
+            }
+        }
+    }
+
+    fn map(&self, c: Vec3<f32>) -> Vec3<f32> {
+        let c = Vec3::new(c.x.max(0.0), c.y.max(0.0), c.z.max(0.0));
+        let mapped = match self.operator {
+            ToneMapOperator::Clamp => c,
+            ToneMapOperator::Reinhard => {
+                Vec3::new(c.x / (1.0 + c.x), c.y / (1.0 + c.y), c.z / (1.0 + c.z))
+            }
+            ToneMapOperator::Aces => aces_fitted(c),
+            ToneMapOperator::Agx => agx(c),
+        };
+        Vec3::new(
+            mapped.x.clamp(0.0, 1.0),
+            mapped.y.clamp(0.0, 1.0),
+            mapped.z.clamp(0.0, 1.0),
+        )
+    }
+}
+
+// 用 log2 亮度直方图求画面的平均亮度，忽略两端的极端像素
+fn histogram_average_luminance(framebuffer: &FrameBuffer, low: f32, high: f32) -> f32 {
+    let mut histogram = [0u32; HISTOGRAM_BINS];
+    let range = HISTOGRAM_MAX_EV - HISTOGRAM_MIN_EV;
+    for pixel in &framebuffer.data {
+        let lum = luminance(pixel.truncate()).max(1e-8);
+        let t = ((lum.log2() - HISTOGRAM_MIN_EV) / range).clamp(0.0, 1.0);
+        let bin = ((t * (HISTOGRAM_BINS - 1) as f32) as usize).min(HISTOGRAM_BINS - 1);
+        histogram[bin] += 1;
+    }
+
+    let total = framebuffer.data.len() as f32;
+    let low_count = total * low.clamp(0.0, 1.0);
+    let high_count = total * high.clamp(low, 1.0);
+
+    // 只累计位于 [low, high] 百分位之间的像素
+    let mut seen = 0.0;
+    let mut weighted_ev = 0.0;
+    let mut weight = 0.0;
+    for (bin, &count) in histogram.iter().enumerate() {
+        let count = count as f32;
+        let start = seen;
+        let end = seen + count;
+        seen = end;
+        let used = (end.min(high_count) - start.max(low_count)).max(0.0);
+        if used > 0.0 {
+            let ev = HISTOGRAM_MIN_EV + (bin as f32 + 0.5) / HISTOGRAM_BINS as f32 * range;
+            weighted_ev += ev * used;
+            weight += used;
+        }
+    }
+
+    if weight <= 0.0 {
+        return MIDDLE_GRAY;
+    }
+    (weighted_ev / weight).exp2()
+}
+
+fn mat3_mul(rows: &[[f32; 3]; 3], v: Vec3<f32>) -> Vec3<f32> {
+    Vec3::new(
+        rows[0][0] * v.x + rows[0][1] * v.y + rows[0][2] * v.z,
+        rows[1][0] * v.x + rows[1][1] * v.y + rows[1][2] * v.z,
+        rows[2][0] * v.x + rows[2][1] * v.y + rows[2][2] * v.z,
+    )
+}
+
+#[rustfmt::skip]
+const ACES_INPUT: [[f32; 3]; 3] = [
+    [0.59719, 0.35458, 0.04823],
+    [0.07600, 0.90834, 0.01566],
+    [0.02840, 0.13383, 0.83777],
+];
+
+#[rustfmt::skip]
+const ACES_OUTPUT: [[f32; 3]; 3] = [
+    [ 1.60475, -0.53108, -0.07367],
+    [-0.10208,  1.10813, -0.00605],
+    [-0.00327, -0.07276,  1.07602],
+];
+
+fn aces_fitted(c: Vec3<f32>) -> Vec3<f32> {
+    let rrt_and_odt = |v: f32| {
+        (v * (v + 0.0245786) - 0.000090537) / (v * (0.983729 * v + 0.432951) + 0.238081)
+    };
+    let v = mat3_mul(&ACES_INPUT, c);
+    let v = Vec3::new(rrt_and_odt(v.x), rrt_and_odt(v.y), rrt_and_odt(v.z));
+    mat3_mul(&ACES_OUTPUT, v)
+}
+
+#[rustfmt::skip]
+const AGX_INSET: [[f32; 3]; 3] = [
+    [0.8424791, 0.0784336, 0.07922375],
+    [0.04232824, 0.8784686, 0.07916613],
+    [0.04237565, 0.0784336, 0.879143],
+];
+
+#[rustfmt::skip]
+const AGX_OUTSET: [[f32; 3]; 3] = [
+    [1.196879, -0.09802088, -0.09902974],
+    [-0.05289685, 1.151903, -0.09896118],
+    [-0.05297164, -0.09804345, 1.151074],
+];
+
+const AGX_MIN_EV: f32 = -12.47393;
+const AGX_MAX_EV: f32 = 4.026069;
+
+fn agx(c: Vec3<f32>) -> Vec3<f32> {
+    // 对数编码后用多项式拟合的 S 曲线，再转换回线性空间
+    let contrast = |x: f32| {
+        let x2 = x * x;
+        let x4 = x2 * x2;
+        15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x
+            - 0.00232
+    };
+    let encode = |v: f32| {
+        let ev = v.max(1e-10).log2().clamp(AGX_MIN_EV, AGX_MAX_EV);
+        contrast((ev - AGX_MIN_EV) / (AGX_MAX_EV - AGX_MIN_EV))
+    };
+    let v = mat3_mul(&AGX_INSET, c);
+    let v = Vec3::new(encode(v.x), encode(v.y), encode(v.z));
+    let v = mat3_mul(&AGX_OUTSET, v);
+    Vec3::new(
+        v.x.max(0.0).powf(2.2),
+        v.y.max(0.0).powf(2.2),
+        v.z.max(0.0).powf(2.2),
+    )
+}
@@ -0,0 +1,158 @@
+use cgmath::{Vector2 as Vec2, Vector3 as Vec3};
+use rayon::prelude::*;
+
+use crate::color::luminance;
+use crate::framebuffer::FrameBuffer;
+use crate::noise;
+use crate::postprocess::{PostContext, PostProcess, linear_depth};
+use crate::texture::{FilterMode, Sampler, Texture, WrapMode};
+
+// 渗色时每个像素的采样数
+const BLEED_SAMPLES: usize = 32;
+
+// 水彩风格化：按深度让颜料从近处向远处渗开，再用颜料浓度模型叠加边缘加深、
+// 纸纹颗粒与低频的浓淡不均。应在色调映射之后的显示颜色上执行
+pub struct Watercolor {
+    pub seed: u32,
+    pub bleed_radius: f32,      // 渗色半径（像素）
+    pub bleed_depth: f32,       // 线性深度的相对差超过该值即视为不同表面，远处的颜色不会渗到近处
+    pub edge_darkening: f32,    // 色块边缘颜料堆积的加深程度
+    pub granulation: f32,       // 颜料沉积在纸纹凹处形成的颗粒感
+    pub turbulence: f32,        // 低频的浓淡不均
+    pub paper: Option<Texture>, // 纸纹图片，为 None 时使用程序生成的噪声
+    pub paper_scale: f32,       // 一张纸纹覆盖的像素数，程序生成的纸纹每张约 64 个颗粒
+}
+
+impl PostProcess for Watercolor {
+    fn name(&self) -> &str {
+        "水彩"
+    }
+
+    fn apply(&self, framebuffer: &mut FrameBuffer, context: &PostContext) {
+        let width = framebuffer.width;
+        let height = framebuffer.height;
+        if width < 3 || height < 3 {
+            return;
+        }
+        let colors: Vec<Vec3<f32>> = framebuffer.data.iter().map(|c| c.truncate()).collect();
+        let depth: Vec<f32> = framebuffer
+            .depth
+            .par_iter()
+            .map(|&d| linear_depth(&context.proj, d))
+            .collect();
+
+        let bled: Vec<Vec3<f32>> = (0..width * height)
+            .into_par_iter()
+            .map(|idx| self.bleed(&colors, &depth, width, height, idx))
+            .collect();
+
+        let result: Vec<Vec3<f32>> = (0..width * height)
+            .into_par_iter()
+            .map(|idx| {
+                let (x, y) = (idx % width, idx / width);
+                let mut density =
+                    1.0 + self.edge_darkening * edge_strength(&bled, width, height, x, y);
+                let (px, py) = (x as f32 / self.paper_scale, y as f32 / self.paper_scale);
+                density *= 1.0 + self.granulation * (self.paper_at(px, py) - 0.5);
+                // 浓淡不均的斑块约为半张纸纹大小，远大于纸纹颗粒
+                let flow = noise::fractal_noise(self.seed ^ 0x5bd1_e995, px * 2.0, py * 2.0, 3);
+                density *= 1.0 + self.turbulence * (flow - 0.5);
+                pigment_density(bled[idx], density)
+            })
+            .collect();
+
+        for (pixel, color) in framebuffer.data.iter_mut().zip(result) {
+            *pixel = color.extend(pixel.w);
+        }
+    }
+}
+
+impl Watercolor {
+    // 在半径随噪声起伏的圆盘内取平均：同一表面与更近的颜色会渗过来，更远的颜色不会，
+    // 因此前景的颜料会越过轮廓渗到背景上，而前景本身的边缘保持完整
+    fn bleed(
+        &self,
+        colors: &[Vec3<f32>],
+        depth: &[f32],
+        width: usize,
+        height: usize,
+        idx: usize,
+    ) -> Vec3<f32> {
+        let (x, y) = (idx % width, idx / width);
+        let center = colors[idx];
+        if self.bleed_radius < 1.0 {
+            return center;
+        }
+        let wobble = noise::fractal_noise(
+            self.seed,
+            x as f32 / (self.bleed_radius * 4.0),
+            y as f32 / (self.bleed_radius * 4.0),
+            2,
+        );
+        let radius = self.bleed_radius * (0.5 + wobble);
+        let z = depth[idx];
+
+        let mut sum = center;
+        let mut total = 1.0;
+        for i in 0..BLEED_SAMPLES {
+            let offset = golden_spiral(i) * radius;
+            let sx = (x as f32 + offset.x).round() as isize;
+            let sy = (y as f32 + offset.y).round() as isize;
+            if sx < 0 || sy < 0 || sx >= width as isize || sy >= height as isize {
+                continue;
+            }
+            let s = sy as usize * width + sx as usize;
+            if (depth[s] - z) / z.max(1e-4) > self.bleed_depth {
+                continue; // 更远的表面
+            }
+            sum += colors[s];
+            total += 1.0;
+        }
+        sum / total
+    }
+
+    // 纸纹高度，[0, 1]，0.5 为平均
+    fn paper_at(&self, x: f32, y: f32) -> f32 {
+        match &self.paper {
+            Some(paper) => {
+                let sampler = Sampler {
+                    wrap_u: WrapMode::Repeat,
+                    wrap_v: WrapMode::Repeat,
+                    filter: FilterMode::Bilinear,
+                    ..Default::default()
+                };
+                let zero = Vec2::new(0.0, 0.0);
+                luminance(paper.sample(&sampler, Vec2::new(x, y), zero, zero))
+            }
+            None => noise::fractal_noise(self.seed ^ 0x27d4_eb2d, x * 64.0, y * 64.0, 3),
+        }
+    }
+}
+
+// 单位圆盘内按黄金角分布的第 i 个采样点
+fn golden_spiral(i: usize) -> Vec2<f32> {
+    const GOLDEN_ANGLE: f32 = 2.399_963;
+    let r = ((i as f32 + 0.5) / BLEED_SAMPLES as f32).sqrt();
+    let theta = i as f32 * GOLDEN_ANGLE;
+    Vec2::new(r * theta.cos(), r * theta.sin())
+}
+
+// 亮度梯度幅值，边缘处颜料堆积
+fn edge_strength(colors: &[Vec3<f32>], width: usize, height: usize, x: usize, y: usize) -> f32 {
+    let at = |dx: isize, dy: isize| {
+        let sx = (x as isize + dx).clamp(0, width as isize - 1) as usize;
+        let sy = (y as isize + dy).clamp(0, height as isize - 1) as usize;
+        luminance(colors[sy * width + sx])
+    };
+    let gx = at(1, 0) - at(-1, 0);
+    let gy = at(0, 1) - at(0, -1);
+    (gx * gx + gy * gy).sqrt()
+}
+
+// Bousseau 等人的颜料浓度模型：density > 1 时颜色变深，< 1 时变淡，纯白与纯黑保持不变
+fn pigment_density(c: Vec3<f32>, density: f32) -> Vec3<f32> {
+    c.map(|v| {
+        let v = v.clamp(0.0, 1.0);
+        (v - (v - v * v) * (density - 1.0)).clamp(0.0, 1.0)
+    })
+}
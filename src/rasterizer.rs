@@ -48,13 +48,66 @@ pub fn interpolate_uv(
     points[0].uv * u + points[1].uv * v + points[2].uv * w
 }
 
+// 屏幕空间中 UV 随 x、y 的变化率（UV 按屏幕空间线性插值，所以整个三角形内是常量）
+pub fn uv_derivatives(points: &[RasterPoint; 3]) -> (Vec2<f32>, Vec2<f32>) {
+    let screen = [points[0].pos, points[1].pos, points[2].pos];
+    let origin = points[0].pos;
+    let uv_at = |p: Vec2<f32>| get_barycentric_coords(&screen, &p).map(|bary| interpolate_uv(points, bary));
+    match (
+        uv_at(origin),
+        uv_at(origin + Vec2::new(1.0, 0.0)),
+        uv_at(origin + Vec2::new(0.0, 1.0)),
+    ) {
+        (Some(uv), Some(uv_x), Some(uv_y)) => (uv_x - uv, uv_y - uv),
+        _ => (Vec2::new(0.0, 0.0), Vec2::new(0.0, 0.0)),
+    }
+}
+
 pub fn interpolate_color(
     points: &[RasterPoint; 3], // 带颜色的三角形三个顶点（屏幕空间）
     bary: (f32, f32, f32),     // 重心坐标 (u, v, w)
 ) -> Vec3<f32> {
     let (u, v, w) = bary;
     // 颜色 = u*v0_color + v*v1_color + w*v2_color
-    points[0].color * w + points[1].color * v + points[2].color * u
+    points[0].color * u + points[1].color * v + points[2].color * w
+}
+
+// 运动矢量：当前像素位置减去插值得到的上一帧屏幕坐标（单位为像素）
+pub fn interpolate_velocity(points: &[RasterPoint; 3], bary: (f32, f32, f32), p: Vec2<f32>) -> Vec2<f32> {
+    let (u, v, w) = bary;
+    p - (points[0].prev_pos * u + points[1].prev_pos * v + points[2].prev_pos * w)
+}
+
+pub fn interpolate_world_pos(points: &[RasterPoint; 3], bary: (f32, f32, f32)) -> Vec3<f32> {
+    let (u, v, w) = bary;
+    points[0].world_pos * u + points[1].world_pos * v + points[2].world_pos * w
+}
+
+pub fn interpolate_object_pos(points: &[RasterPoint; 3], bary: (f32, f32, f32)) -> Vec3<f32> {
+    let (u, v, w) = bary;
+    points[0].object_pos * u + points[1].object_pos * v + points[2].object_pos * w
+}
+
+// 根据三角形的世界坐标和 UV 求切线 T (dP/du) 与副切线 B (dP/dv)
+pub fn compute_tangent_frame(points: &[RasterPoint; 3]) -> (Vec3<f32>, Vec3<f32>) {
+    let edge1 = points[1].world_pos - points[0].world_pos;
+    let edge2 = points[2].world_pos - points[0].world_pos;
+    let duv1 = points[1].uv - points[0].uv;
+    let duv2 = points[2].uv - points[0].uv;
+
+    let det = duv1.x * duv2.y - duv2.x * duv1.y;
+    if det.abs() < 1e-8 {
+        // UV 退化（如没有贴图坐标）时，随便取一组与面垂直的正交基
+        let normal = edge1.cross(edge2);
+        let helper = if normal.x.abs() < 0.9 { Vec3::unit_x() } else { Vec3::unit_y() };
+        let tangent = helper.cross(normal).normalize();
+        let bitangent = normal.cross(tangent).normalize();
+        return (tangent, bitangent);
+    }
+
+    let tangent = (edge1 * duv2.y - edge2 * duv1.y) / det;
+    let bitangent = (edge2 * duv1.x - edge1 * duv2.x) / det;
+    (tangent.normalize(), bitangent.normalize())
 }
 
 pub fn interpolate_normal(points: &[RasterPoint; 3], bary: (f32, f32, f32)) -> Vec3<f32> {
@@ -108,3 +161,84 @@ pub fn is_inside_triangle(vertices: &[Vec2<f32>; 3], p: &Vec2<f32>) -> bool {
     (cross0 >= 0.0 && cross1 >= 0.0 && cross2 >= 0.0)
         || (cross0 <= 0.0 && cross1 <= 0.0 && cross2 <= 0.0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(pos: Vec2<f32>, world_pos: Vec3<f32>, uv: Vec2<f32>, color: Vec3<f32>, normal: Vec3<f32>) -> RasterPoint {
+        RasterPoint {
+            pos,
+            prev_pos: pos,
+            world_pos,
+            object_pos: world_pos * 2.0,
+            color,
+            normal,
+            z: 1.0 + world_pos.x,
+            uv,
+        }
+    }
+
+    // 屏幕坐标、世界坐标与 UV 都按 uv = pos / 10 线性对应
+    fn triangle() -> [RasterPoint; 3] {
+        [
+            point(
+                Vec2::new(0.0, 0.0),
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec2::new(0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::unit_x(),
+            ),
+            point(
+                Vec2::new(10.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec2::new(1.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                Vec3::unit_y(),
+            ),
+            point(
+                Vec2::new(0.0, 10.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                Vec2::new(0.0, 1.0),
+                Vec3::new(0.0, 0.0, 1.0),
+                Vec3::unit_z(),
+            ),
+        ]
+    }
+
+    fn close2(a: Vec2<f32>, b: Vec2<f32>) -> bool {
+        (a - b).magnitude() < 1e-5
+    }
+
+    fn close3(a: Vec3<f32>, b: Vec3<f32>) -> bool {
+        (a - b).magnitude() < 1e-5
+    }
+
+    #[test]
+    fn corners_reproduce_vertex_values() {
+        let points = triangle();
+        let screen = [points[0].pos, points[1].pos, points[2].pos];
+        for (i, p) in points.iter().enumerate() {
+            let bary = get_barycentric_coords(&screen, &p.pos).unwrap();
+            assert!(close3(interpolate_color(&points, bary), p.color), "color at corner {i}");
+            assert!(close3(interpolate_world_pos(&points, bary), p.world_pos), "world_pos at corner {i}");
+            assert!(close3(interpolate_object_pos(&points, bary), p.object_pos), "object_pos at corner {i}");
+            assert!(close3(interpolate_normal(&points, bary), p.normal), "normal at corner {i}");
+            assert!(close2(interpolate_uv(&points, bary), p.uv), "uv at corner {i}");
+            assert!(close2(interpolate_velocity(&points, bary, p.pos), Vec2::new(0.0, 0.0)), "velocity at corner {i}");
+            assert!((interpolate_depth(&points, bary) - p.z).abs() < 1e-5, "depth at corner {i}");
+        }
+    }
+
+    #[test]
+    fn uv_derivatives_and_tangent_frame_follow_the_mapping() {
+        let points = triangle();
+        let (uv_dx, uv_dy) = uv_derivatives(&points);
+        assert!(close2(uv_dx, Vec2::new(0.1, 0.0)));
+        assert!(close2(uv_dy, Vec2::new(0.0, 0.1)));
+
+        let (tangent, bitangent) = compute_tangent_frame(&points);
+        assert!(close3(tangent, Vec3::unit_x()));
+        assert!(close3(bitangent, Vec3::unit_y()));
+    }
+}
@@ -1,21 +1,36 @@
+pub mod cel;
 pub mod clip;
 pub mod fragment_shader;
+pub mod gbuffer;
+pub mod hair;
+pub mod hatching;
+pub mod parallax;
 pub mod vertex_shader;
 
-use crate::BLACK;
-use crate::renderer::fragment_shader::InkShader;
-use crate::texture::Texture;
+use crate::renderer::fragment_shader::{GoochShader, GoochTone, InkNoise, InkShader};
+use crate::texture::{Texture, TextureBinding};
 use crate::vertex::{ClipSpaceVertex, Material, RasterPoint, RasterTriangle, Triangle};
 use crate::{camera, framebuffer, rasterizer};
 use camera::Camera;
 use cgmath::{InnerSpace, Matrix, Matrix4 as Mat4, SquareMatrix};
-use cgmath::{Vector2 as Vec2, Vector3 as Vec3};
+use cgmath::{Vector2 as Vec2, Vector3 as Vec3, Vector4 as Vec4};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use fragment_shader::{FragmentData, FragmentShader, NormalDebugShader, PhongShader, ToonShader};
-use framebuffer::FrameBuffer;
-
+use std::sync::Arc;
+use fragment_shader::{
+    AnisotropyDebugShader, CelShader, FragmentData, FragmentShader, HairShader, HatchingShader,
+    MatcapShader, NormalDebugShader, PhongShader, ToonShader, UnlitShader,
+};
+use framebuffer::{BACKGROUND_ID, FrameBuffer, PixelGeometry};
+
+use self::cel::CelStyle;
 use self::clip::{Clipper, SimpleClipper};
-use self::vertex_shader::{DefaultVertexShader, VertexShader, VertexShaderUniforms};
+use self::gbuffer::GBuffer;
+use self::hair::HairStyle;
+use self::hatching::Hatching;
+use self::parallax::ParallaxMap;
+use self::vertex_shader::{
+    DefaultVertexShader, HullOutline, OutlineVertexShader, VertexShader, VertexShaderUniforms,
+};
 
 //use crate::renderer_debug::RendererDebugUtils; // 已经被迁移出去的旧函数
 
@@ -54,16 +69,49 @@ impl Light {
     }
 }
 
+// 渲染路径
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderPath {
+    Forward,  // 光栅化时逐片元着色，被遮挡的片元也会着色
+    Deferred, // 先写 G-buffer，再对每个可见像素着色一次
+}
+
+// 绘制单个模型时绑定的资源
+#[derive(Clone, Copy, Default)]
+pub struct DrawResources<'a> {
+    pub texture: Option<TextureBinding<'a>>,
+    pub parallax: Option<&'a ParallaxMap>,
+    pub matcap: Option<&'a Arc<Texture>>, // matcap 着色器使用的贴图，延迟渲染时由 G-buffer 的表面持有
+    pub object_id: u32, // 写入 object_id 缓冲的物体编号，不能与背景的 BACKGROUND_ID 相同
+}
+
 pub struct Renderer {
     pub(crate) camera: Camera,
     pub(crate) framebuffer: FrameBuffer,
     pub(crate) viewport: Viewport,
     pub(crate) light: Light,
+    // 为 true 时只写入深度与法线，不执行片元着色（SSAO 预渲染使用）
+    pub(crate) depth_prepass: bool,
+    // 上一帧的观察投影矩阵，与每个模型上一帧的模型矩阵一起求运动矢量
+    pub(crate) prev_view_proj: Mat4<f32>,
+    // 为 Some 时使用延迟渲染：绘制时只填充 G-buffer，之后由 shade_deferred 对每个可见像素着色一次
+    pub(crate) gbuffer: Option<GBuffer>,
+    // 素描排线着色器使用的色调图与纸纹，着色器方法为 "hatching" 时才会创建
+    pub(crate) hatching: Option<Arc<Hatching>>,
+    // 可配置卡通着色器的参数，着色器方法为 "cel" 时才会创建
+    pub(crate) cel: Option<Arc<CelStyle>>,
+    // 头发着色器的发丝走向与高光参数，模型或子网格使用 "hair" 着色器时才会创建
+    pub(crate) hair: Option<Arc<HairStyle>>,
+    // 水墨着色器随机墨点的种子与格子大小
+    pub(crate) ink_noise: InkNoise,
+    // Gooch 着色器的冷暖色调
+    pub(crate) gooch: GoochTone,
 }
 
 impl Renderer {
     pub fn new(camera: Camera, w: usize, h: usize) -> Self {
         let framebuffer = FrameBuffer::new(w, h);
+        let prev_view_proj = camera.get_view_proj_mat();
         Self {
             camera,
             framebuffer,
@@ -74,6 +122,14 @@ impl Renderer {
                 h: h as i32,
             },
             light: Light::default(),
+            depth_prepass: false,
+            prev_view_proj,
+            gbuffer: None,
+            hatching: None,
+            cel: None,
+            hair: None,
+            ink_noise: InkNoise::default(),
+            gooch: GoochTone::default(),
         }
     }
     //一统江山后的完整渲染管线
@@ -81,7 +137,8 @@ impl Renderer {
         &mut self,
         triangles: &mut Vec<Triangle>,
         model: &Mat4<f32>,
-        texture: Option<&Texture>,
+        prev_model: &Mat4<f32>,
+        resources: DrawResources,
         shader_name: &str,
     ) {
         println!("三角形数量: {}", triangles.len());
@@ -90,21 +147,17 @@ impl Renderer {
         let view_matrix = self.camera.get_view_mat();
         let proj_matrix = self.camera.get_frustum().get_mat();
         let mvp_matrix = proj_matrix * view_matrix * model;
+        let prev_mvp_matrix = self.prev_view_proj * prev_model;
 
         // 初始化本次渲染所使用的模块
         let vertex_shader = DefaultVertexShader;
         let clipper = SimpleClipper;
-        let fragment_shader: Box<dyn FragmentShader> = match shader_name {
-            "toon" => Box::new(ToonShader { light: self.light }),
-            "ink" => Box::new(InkShader { light: self.light }),
-            "phong" => Box::new(PhongShader { light: self.light }),
-            "normal" => Box::new(NormalDebugShader),
-            _ => Box::new(ToonShader { light: self.light }),
-        };
+        let fragment_shader = self.fragment_shader(shader_name);
 
         let uniforms = VertexShaderUniforms {
             model_matrix: model,
             mvp_matrix: &mvp_matrix,
+            prev_mvp_matrix: &prev_mvp_matrix,
             normal_matrix: &normal_matrix,
         };
 
@@ -124,13 +177,215 @@ impl Renderer {
             //管线阶段 3: 裁剪
             let clipped_triangles = clipper.clip_triangle(&clip_space_triangle);
 
+            // 延迟渲染时登记该三角形的表面（材质、着色器与 matcap 贴图）
+            let surface_id = match self.gbuffer.as_mut() {
+                Some(gbuffer) => {
+                    gbuffer.surface_id(&triangle.material, shader_name, resources.matcap)
+                }
+                None => 0,
+            };
+
             for clipped_triangle_verts in clipped_triangles {
                 // 阶段 4: 屏幕映射
                 let raster_triangle =
                     self.viewport_transform(&clipped_triangle_verts, triangle.material);
 
                 // 阶段 5: 光栅化和像素着色
-                self.rasterize_triangle(&raster_triangle, texture, &*fragment_shader);
+                self.rasterize_triangle(&raster_triangle, resources, &*fragment_shader, surface_id);
+            }
+        }
+    }
+
+    // 反转外壳描边：把模型沿顶点法线外扩后再画一遍，剔除正面只保留背面，并填充描边颜色。
+    // 外壳被模型本身挡住的部分通不过深度测试，只在轮廓外和前后重叠处露出线条
+    pub fn render_outline_hull(
+        &mut self,
+        triangles: &[Triangle],
+        model: &Mat4<f32>,
+        prev_model: &Mat4<f32>,
+        object_id: u32,
+        outline: &HullOutline,
+    ) {
+        // 外壳不参与 SSAO 的深度预渲染
+        if self.depth_prepass {
+            return;
+        }
+        let normal_matrix = model.invert().unwrap().transpose();
+        let view_proj = self.camera.get_view_proj_mat();
+        let proj_matrix = self.camera.get_frustum().get_mat();
+        let mvp_matrix = view_proj * model;
+        let prev_mvp_matrix = self.prev_view_proj * prev_model;
+
+        let vertex_shader = OutlineVertexShader {
+            outline: *outline,
+            view_proj,
+            pixel_scale: 2.0 / (proj_matrix.y.y * self.viewport.h as f32),
+        };
+        let clipper = SimpleClipper;
+        let fragment_shader = UnlitShader;
+        let resources = DrawResources {
+            object_id,
+            ..Default::default()
+        };
+
+        let uniforms = VertexShaderUniforms {
+            model_matrix: model,
+            mvp_matrix: &mvp_matrix,
+            prev_mvp_matrix: &prev_mvp_matrix,
+            normal_matrix: &normal_matrix,
+        };
+
+        for triangle in triangles {
+            // 正面剔除：只保留背对相机的面
+            let world_pos =
+                (uniforms.model_matrix * triangle.vertices[0].pos.extend(1.0)).truncate();
+            let view_dir = (self.camera.eye - world_pos).normalize();
+            let tri_normal = (uniforms.normal_matrix * triangle.normal.extend(0.0)).truncate();
+            if view_dir.dot(tri_normal) > 0.0 {
+                continue;
+            }
+
+            let clip_space_triangle = vertex_shader.shade_triangle(triangle, &uniforms);
+            let clipped_triangles = clipper.clip_triangle(&clip_space_triangle);
+            let surface_id = match self.gbuffer.as_mut() {
+                Some(gbuffer) => gbuffer.surface_id(&triangle.material, "unlit", None),
+                None => 0,
+            };
+
+            for clipped_triangle_verts in clipped_triangles {
+                let raster_triangle =
+                    self.viewport_transform(&clipped_triangle_verts, triangle.material);
+                self.rasterize_triangle(&raster_triangle, resources, &fragment_shader, surface_id);
+            }
+        }
+    }
+
+    pub fn set_render_path(&mut self, path: RenderPath) {
+        self.gbuffer = match path {
+            RenderPath::Forward => None,
+            RenderPath::Deferred => Some(GBuffer::new(
+                self.framebuffer.width,
+                self.framebuffer.height,
+            )),
+        };
+    }
+
+    fn fragment_shader(&self, shader_name: &str) -> Box<dyn FragmentShader> {
+        match shader_name {
+            "toon" => Box::new(ToonShader { light: self.light }),
+            "ink" => Box::new(InkShader {
+                light: self.light,
+                noise: self.ink_noise,
+            }),
+            "phong" => Box::new(PhongShader { light: self.light }),
+            "matcap" => Box::new(MatcapShader),
+            "gooch" => Box::new(GoochShader {
+                light: self.light,
+                tone: self.gooch,
+            }),
+            "glitch" => Box::new(PhongShader { light: self.light }), // 故障效果在后处理中添加
+            // 绘画风格在后处理中添加
+            "kuwahara" | "watercolor" => Box::new(PhongShader { light: self.light }),
+            // 网点与抖动在后处理中添加
+            "halftone" | "dither" | "pixel_art" => Box::new(PhongShader { light: self.light }),
+            "cel" => match &self.cel {
+                Some(style) => Box::new(CelShader {
+                    light: self.light,
+                    style: Arc::clone(style),
+                }),
+                None => {
+                    println!("未设置卡通渲染参数，改用默认卡通着色");
+                    Box::new(ToonShader { light: self.light })
+                }
+            },
+            "hair" => match &self.hair {
+                Some(style) => Box::new(HairShader {
+                    light: self.light,
+                    style: Arc::clone(style),
+                }),
+                None => {
+                    println!("未设置头发着色参数，改用卡通着色");
+                    Box::new(ToonShader { light: self.light })
+                }
+            },
+            "hatching" => match &self.hatching {
+                Some(hatching) => Box::new(HatchingShader {
+                    light: self.light,
+                    hatching: Arc::clone(hatching),
+                }),
+                None => {
+                    println!("未设置素描排线的色调图，改用卡通着色");
+                    Box::new(ToonShader { light: self.light })
+                }
+            },
+            "unlit" => Box::new(UnlitShader),
+            "normal" => Box::new(NormalDebugShader),
+            "anisotropy" => Box::new(AnisotropyDebugShader),
+            _ => Box::new(ToonShader { light: self.light }),
+        }
+    }
+
+    // 延迟渲染的光照阶段：由 G-buffer 与深度重建每个可见像素的片元数据，交给对应的着色器计算一次。
+    // 纹理已在几何阶段采样为 albedo，因此片元数据中不再带纹理
+    pub fn shade_deferred(&mut self) {
+        let Some(gbuffer) = &self.gbuffer else {
+            return;
+        };
+        let shaders: Vec<Box<dyn FragmentShader>> = gbuffer
+            .surfaces
+            .iter()
+            .map(|surface| self.fragment_shader(&surface.shader))
+            .collect();
+        let inv_view_proj = self.camera.get_view_proj_mat().invert().unwrap();
+        let view_matrix = self.camera.get_view_mat();
+        let camera_pos = self.camera.eye;
+        let framebuffer = &self.framebuffer;
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        let (viewport_w, viewport_h) = (self.viewport.w as f32, self.viewport.h as f32);
+
+        let colors: Vec<Option<Vec3<f32>>> = (0..width * height)
+            .into_par_iter()
+            .map(|idx| {
+                if framebuffer.object_id[idx] == BACKGROUND_ID {
+                    return None;
+                }
+                // 屏幕映射的逆变换，得到世界坐标
+                let (x, y) = ((idx % width) as f32 + 0.5, (idx / width) as f32 + 0.5);
+                let ndc = Vec4::new(
+                    x / viewport_w * 2.0 - 1.0,
+                    1.0 - y / viewport_h * 2.0,
+                    framebuffer.depth[idx] * 2.0 - 1.0,
+                    1.0,
+                );
+                let world = inv_view_proj * ndc;
+                let surface_id = gbuffer.surface_id[idx] as usize;
+                let surface = &gbuffer.surfaces[surface_id];
+                let data = FragmentData {
+                    world_pos: world.truncate() / world.w,
+                    object_pos: gbuffer.object_pos[idx],
+                    normal: framebuffer.normal[idx],
+                    uv: gbuffer.uv[idx],
+                    uv_dx: gbuffer.uv_dx[idx],
+                    uv_dy: gbuffer.uv_dy[idx],
+                    color: gbuffer.albedo[idx],
+                    texture: None,
+                    material: &surface.material,
+                    matcap: surface.matcap.as_deref(),
+                    camera_pos,
+                    view_matrix,
+                    tangent: gbuffer.tangent[idx],
+                    bitangent: gbuffer.bitangent[idx],
+                    shadow: gbuffer.shadow[idx],
+                    ambient_occlusion: framebuffer.ambient_occlusion[idx],
+                    screen_pos: Vec2::new(x, y),
+                };
+                Some(shaders[surface_id].shade(data))
+            })
+            .collect();
+
+        for (pixel, color) in self.framebuffer.data.iter_mut().zip(colors) {
+            if let Some(color) = color {
+                *pixel = color.extend(1.0);
             }
         }
     }
@@ -142,23 +397,16 @@ impl Renderer {
         material: Material,
     ) -> RasterTriangle {
         let raster_vertices = clip_triangle.map(|clip_v| {
-            let mut ndc_pos = clip_v.position;
-
-            // 1. 透视除法
-            ndc_pos /= clip_v.position.w;
-
-            // 转换到屏幕空间
-            let screen_x =
-                (ndc_pos.x + 1.0) * 0.5 * self.viewport.w as f32 + self.viewport.x as f32;
-            let screen_y = self.viewport.h as f32
-                - (ndc_pos.y + 1.0) * 0.5 * self.viewport.h as f32
-                + self.viewport.y as f32;
+            let (screen, z) = self.to_screen(clip_v.position);
+            let (prev_screen, _) = self.to_screen(clip_v.prev_position);
 
             RasterPoint {
-                pos: Vec2::new(screen_x, screen_y),
-                z: (ndc_pos.z + 1.0) * 0.5,
+                pos: screen,
+                prev_pos: prev_screen,
+                z,
                 // 继承其他属性
                 world_pos: clip_v.world_pos,
+                object_pos: clip_v.object_pos,
                 normal: clip_v.normal,
                 uv: clip_v.uv,
                 color: clip_v.color,
@@ -171,16 +419,31 @@ impl Renderer {
         }
     }
 
+    // 透视除法后转换到屏幕空间，返回 (屏幕坐标, [0, 1] 深度)
+    fn to_screen(&self, clip_pos: Vec4<f32>) -> (Vec2<f32>, f32) {
+        let ndc_pos = clip_pos / clip_pos.w;
+        let screen_x = (ndc_pos.x + 1.0) * 0.5 * self.viewport.w as f32 + self.viewport.x as f32;
+        let screen_y = self.viewport.h as f32 - (ndc_pos.y + 1.0) * 0.5 * self.viewport.h as f32
+            + self.viewport.y as f32;
+        (Vec2::new(screen_x, screen_y), (ndc_pos.z + 1.0) * 0.5)
+    }
+
     // 进行光栅化
     pub fn rasterize_triangle(
         &mut self,
         triangle: &RasterTriangle,
-        texture: Option<&Texture>,
+        resources: DrawResources,
         shader: &dyn FragmentShader, // 接收一个Shader
+        surface_id: u32,             // 延迟渲染时写入 G-buffer 的表面编号
     ) {
         let points = &triangle.vertices;
         let (min_x, min_y, max_x, max_y) =
             rasterizer::get_box(&[points[0].pos, points[1].pos, points[2].pos]);
+        // 切线空间每个三角形只需计算一次
+        let (tangent, bitangent) = rasterizer::compute_tangent_frame(points);
+        let (uv_dx, uv_dy) = rasterizer::uv_derivatives(points);
+        let to_light = -self.light.direction;
+        let view_matrix = self.camera.get_view_mat();
 
         for y in min_y..=max_y {
             for x in min_x..=max_x {
@@ -200,186 +463,68 @@ impl Renderer {
                     let interpolated_depth = rasterizer::interpolate_depth(points, bary);
                     let interpolated_normal = rasterizer::interpolate_normal(points, bary);
                     let interpolated_uv = rasterizer::interpolate_uv(points, bary);
-                    let interpolated_world_pos = {
-                        let p0 = points[0].world_pos * bary.2;
-                        let p1 = points[1].world_pos * bary.1;
-                        let p2 = points[2].world_pos * bary.0;
-                        p0 + p1 + p2
+                    let interpolated_world_pos = rasterizer::interpolate_world_pos(points, bary);
+                    let interpolated_object_pos = rasterizer::interpolate_object_pos(points, bary);
+                    let velocity = rasterizer::interpolate_velocity(points, bary, p);
+
+                    let geometry = PixelGeometry {
+                        depth: interpolated_depth,
+                        normal: interpolated_normal,
+                        velocity,
+                        object_id: resources.object_id,
                     };
+                    if self.depth_prepass {
+                        self.framebuffer.put_depth(x as usize, y as usize, geometry);
+                        continue;
+                    }
+                    // 延迟渲染先做深度测试，被遮挡的片元连纹理也不用采样
+                    if self.gbuffer.is_some()
+                        && !self.framebuffer.depth_test(x as usize, y as usize, interpolated_depth)
+                    {
+                        continue;
+                    }
 
                     // 打包成 FragmentData
-                    let fragment_data = FragmentData {
+                    let mut fragment_data = FragmentData {
                         world_pos: interpolated_world_pos,
+                        object_pos: interpolated_object_pos,
                         normal: interpolated_normal,
                         uv: interpolated_uv,
+                        uv_dx,
+                        uv_dy,
                         color: interpolated_color,
-                        texture,
+                        texture: resources.texture,
                         material: &triangle.material,
+                        matcap: resources.matcap.map(|matcap| &**matcap),
                         camera_pos: self.camera.eye,
+                        view_matrix,
+                        tangent,
+                        bitangent,
+                        shadow: 1.0,
+                        ambient_occlusion: self
+                            .framebuffer
+                            .ambient_occlusion_at(x as usize, y as usize),
+                        screen_pos: Vec2::new(x as f32 + 0.5, y as f32 + 0.5),
                     };
 
-                    // 调用 shader 来获取颜色！
-                    let color = shader.shade(fragment_data);
-
-                    self.framebuffer.put_pixel(
-                        x as usize,
-                        y as usize,
-                        color.extend(1.0),
-                        interpolated_depth,
-                    );
-                }
-            }
-        }
-    }
-
-    pub fn draw_depth_outline_sobel(&mut self, threshold: f32, line_width: usize) {
-        // 定义Prewitt算子的水平和垂直卷积核（3x3二维数组）
-        let sobel_x = [[-1.0, 0.0, 1.0], [-2.0, 0.0, 2.0], [-1.0, 0.0, 1.0]]; // x方向Sobel核
-        let sobel_y = [[-1.0, -2.0, -1.0], [0.0, 0.0, 0.0], [1.0, 2.0, 1.0]]; // y方向Sobel核
-
-        let width = self.framebuffer.width;
-        let height = self.framebuffer.height;
-        let depth_buffer = self.framebuffer.depth.clone();
-
-        // 将一维深度缓冲转换为二维数组（y行x列）
-        let mut depth_matrix = vec![vec![0.0; width]; height];
-        for y in 0..height {
-            for x in 0..width {
-                depth_matrix[y][x] = depth_buffer[y * width + x];
-            }
-        }
-
-        let mut outline_pixels = Vec::new();
-
-        // 遍历深度矩阵计算梯度（避开边界像素）
-        for y in 1..height - 1 {
-            for x in 1..width - 1 {
-                let current_depth = depth_matrix[y][x];
-                const BACKGROUND_DEPTH: f32 = 1.0; // 关键：替换为你渲染管线中的背景深度值
-                if (current_depth - BACKGROUND_DEPTH).abs() < 1e-6 {
-                    // 允许微小误差（浮点精度问题）
-                    continue; // 背景像素不参与边缘检测
-                }
-                // 初始化梯度值
-                let mut gx = 0.0;
-                let mut gy = 0.0;
-
-                // 3x3邻域卷积运算
-                for ky in 0..3 {
-                    for kx in 0..3 {
-                        // 计算邻域像素坐标（相对于当前像素的偏移）
-                        let ny = (y as i32 + (ky as i32 - 1)) as usize;
-                        let nx = (x as i32 + (kx as i32 - 1)) as usize;
-
-                        // 累加梯度值：卷积核权重 × 深度值
-                        gx += sobel_x[ky][kx] * depth_matrix[ny][nx];
-                        gy += sobel_y[ky][kx] * depth_matrix[ny][nx];
+                    // 视差遮蔽：在着色前偏移 UV
+                    if let Some(parallax) = resources.parallax {
+                        parallax.apply(&mut fragment_data, to_light);
                     }
-                }
 
-                // 计算梯度幅值（使用简化版 |Gx| + |Gy|）
-                let gradient_mag = gx.abs() + gy.abs();
-
-                // 判断是否为边缘像素
-                if gradient_mag > threshold {
-                    outline_pixels.push((x, y));
-                }
-            }
-        }
-
-        // 绘制轮廓线
-        for &(x, y) in &outline_pixels {
-            // 绘制指定线宽的轮廓
-            for dy in 0..line_width {
-                for dx in 0..line_width {
-                    let draw_x = x + dx;
-                    let draw_y = y + dy;
-                    if draw_x < width && draw_y < height {
-                        let index = draw_y * width + draw_x;
-                        // 设置轮廓颜色为黑色（RGBA）
-                        self.framebuffer.data[index] = BLACK;
+                    // 延迟渲染的几何阶段：只记录表面属性，光照留到 shade_deferred 中计算
+                    if let Some(gbuffer) = self.gbuffer.as_mut() {
+                        let idx = y as usize * self.framebuffer.width + x as usize;
+                        gbuffer.write(idx, &fragment_data, surface_id);
+                        self.framebuffer.put_depth(x as usize, y as usize, geometry);
+                        continue;
                     }
-                }
-            }
-        }
-    }
-
-    pub fn draw_color_outline_sobel(&mut self, threshold: f32, line_width: usize) {
-        // Sobel
-        let sobel_x = [[-1.0, 0.0, 1.0], [-2.0, 0.0, 2.0], [-1.0, 0.0, 1.0]]; // x方向Sobel核
-        let sobel_y = [[-1.0, -2.0, -1.0], [0.0, 0.0, 0.0], [1.0, 2.0, 1.0]]; // y方向Sobel核
-
-        let width = self.framebuffer.width;
-        let height = self.framebuffer.height;
-        let color_buffer = self.framebuffer.data.clone(); // 此时 data 是 Vec<u32>
-
-        // 解析 u32 颜色为 RGB 通道（0.0~1.0 范围），存储为二维矩阵
-        let mut color_matrix = vec![vec![[0.0; 3]; width]; height]; // [y][x] -> [r, g, b]
-        for y in 0..height {
-            for x in 0..width {
-                let idx = y * width + x;
-                let argb = color_buffer[idx]; // 假设为 ARGB8888 格式
-
-                // 提取 RGB 通道（8位转 0.0~1.0）
-                let r = argb.x;
-                let g = argb.y;
-                let b = argb.z;
-
-                color_matrix[y][x] = [r, g, b];
-            }
-        }
 
-        let mut outline_pixels = Vec::new();
-
-        // 遍历计算颜色梯度（避开边界像素）
-        for y in 1..height - 1 {
-            for x in 1..width - 1 {
-                // 计算 RGB 三个通道的梯度
-                let (mut gx_r, mut gy_r) = (0.0, 0.0);
-                let (mut gx_g, mut gy_g) = (0.0, 0.0);
-                let (mut gx_b, mut gy_b) = (0.0, 0.0);
-
-                // 3x3 邻域卷积
-                for ky in 0..3 {
-                    for kx in 0..3 {
-                        let ny = (y as i32 + (ky as i32 - 1)) as usize;
-                        let nx = (x as i32 + (kx as i32 - 1)) as usize;
-
-                        // 对每个颜色通道应用卷积核
-                        gx_r += sobel_x[ky][kx] * color_matrix[ny][nx][0];
-                        gy_r += sobel_y[ky][kx] * color_matrix[ny][nx][0];
-
-                        gx_g += sobel_x[ky][kx] * color_matrix[ny][nx][1];
-                        gy_g += sobel_y[ky][kx] * color_matrix[ny][nx][1];
-
-                        gx_b += sobel_x[ky][kx] * color_matrix[ny][nx][2];
-                        gy_b += sobel_y[ky][kx] * color_matrix[ny][nx][2];
-                    }
-                }
-
-                // 计算梯度幅值（取三通道最大值）
-                let mag_r = gx_r.abs() + gy_r.abs();
-                let mag_g = gx_g.abs() + gy_g.abs();
-                let mag_b = gx_b.abs() + gy_b.abs();
-                let gradient_mag = mag_r.max(mag_g).max(mag_b);
-
-                // 超过阈值则视为边缘
-                if gradient_mag > threshold {
-                    outline_pixels.push((x, y));
-                }
-            }
-        }
+                    // 调用 shader 来获取颜色！
+                    let color = shader.shade(fragment_data);
 
-        // 绘制描边（直接操作 u32 颜色）
-        for &(x, y) in &outline_pixels {
-            for dy in 0..line_width {
-                for dx in 0..line_width {
-                    let draw_x = x + dx;
-                    let draw_y = y + dy;
-                    if draw_x < width && draw_y < height {
-                        let index = draw_y * width + draw_x;
-                        self.framebuffer.data[index] = BLACK; // 直接设置 u32 颜色
-                    }
+                    self.framebuffer
+                        .put_pixel(x as usize, y as usize, color.extend(1.0), geometry);
                 }
             }
         }
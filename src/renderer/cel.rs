@@ -0,0 +1,95 @@
+use cgmath::{Vector2 as Vec2, Vector3 as Vec3};
+
+use crate::texture::{FilterMode, Sampler, Texture, WrapMode};
+
+// 一条色阶：光照项不低于 threshold 时使用 color
+#[derive(Debug, Clone, Copy)]
+pub struct CelBand {
+    pub threshold: f32,
+    pub color: Vec3<f32>,
+}
+
+// 漫反射的明暗响应，结果为从阴影色 (0) 到受光色 (1) 的混合权重
+pub enum CelRamp {
+    Bands(Vec<CelBand>), // 按阈值从小到大排列，低于第一条阈值的部分为纯阴影
+    // 横向按光照项取值；纵向按表面朝向相机的程度 (n·v) 取值，底部为轮廓处，顶部为正对相机处，
+    // 高度为 1 的图片即一维色阶
+    Texture(Texture),
+}
+
+// 风格化高光的形状
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpecularShape {
+    None,
+    Soft, // 普通的 Blinn-Phong 高光
+    Hard, // 边缘锐利的色块，大小与柔和程度可调
+}
+
+// 卡通渲染的参数：色阶、阴影色、边缘光与高光形状
+pub struct CelStyle {
+    pub ramp: CelRamp,
+    pub half_lambert: bool, // 光照项使用 n·l * 0.5 + 0.5，暗部过渡更平缓
+    pub shadow_color: Vec3<f32>,
+    pub rim_color: Vec3<f32>,
+    pub rim_strength: f32, // 为 0 时不加边缘光
+    pub rim_power: f32,    // 菲涅尔项 (1 - n·v) 的指数，越大边缘光越窄
+    pub rim_threshold: f32,
+    pub rim_softness: f32, // 为 0 时边缘光是一条硬边
+    pub specular_shape: SpecularShape,
+    pub specular_size: f32, // Hard 形状下高光色块的大小（0 ~ 1）
+    pub specular_softness: f32,
+}
+
+impl CelStyle {
+    // 由光照项与朝向求出阴影色到受光色的混合权重
+    pub fn ramp(&self, light: f32, facing: f32) -> Vec3<f32> {
+        match &self.ramp {
+            CelRamp::Bands(bands) => bands
+                .iter()
+                .rev()
+                .find(|band| light >= band.threshold)
+                .map_or(Vec3::new(0.0, 0.0, 0.0), |band| band.color),
+            CelRamp::Texture(texture) => {
+                let sampler = Sampler {
+                    wrap_u: WrapMode::ClampToEdge,
+                    wrap_v: WrapMode::ClampToEdge,
+                    filter: FilterMode::Bilinear,
+                    ..Default::default()
+                };
+                let zero = Vec2::new(0.0, 0.0);
+                texture.sample(&sampler, Vec2::new(light, facing), zero, zero)
+            }
+        }
+    }
+
+    // 菲涅尔边缘光的强度
+    pub fn rim(&self, facing: f32) -> f32 {
+        if self.rim_strength <= 0.0 {
+            return 0.0;
+        }
+        let fresnel = (1.0 - facing).clamp(0.0, 1.0).powf(self.rim_power);
+        step(self.rim_threshold, self.rim_softness, fresnel) * self.rim_strength
+    }
+
+    // 高光强度，spec 为 n·h，shininess 为材质的反光度
+    pub fn specular(&self, spec: f32, shininess: f32) -> f32 {
+        match self.specular_shape {
+            SpecularShape::None => 0.0,
+            SpecularShape::Soft => spec.powf(shininess),
+            SpecularShape::Hard => {
+                // 色块大小换算到 n·h 的阈值：size 越大，阈值越低
+                let threshold = 1.0 - self.specular_size.clamp(0.0, 1.0).powi(2) * 0.5;
+                step(threshold, self.specular_softness * (1.0 - threshold), spec)
+            }
+        }
+    }
+}
+
+// softness 为 0 时为阶跃函数，否则在 threshold 两侧 softness 宽的范围内平滑过渡
+pub fn step(threshold: f32, softness: f32, x: f32) -> f32 {
+    if softness <= 0.0 {
+        return if x >= threshold { 1.0 } else { 0.0 };
+    }
+    let t = ((x - threshold) / (2.0 * softness) + 0.5).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
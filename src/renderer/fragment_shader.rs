@@ -1,27 +1,70 @@
-use cgmath::{ElementWise, InnerSpace, Vector2 as Vec2, Vector3 as Vec3};
-use rand::Rng;
+use cgmath::{ElementWise, InnerSpace, Matrix4 as Mat4, Vector2 as Vec2, Vector3 as Vec3};
 
+use std::sync::Arc;
+
+use crate::color;
+use crate::noise;
 use crate::renderer::Light; // 从 renderer 模块导入 Light
-use crate::texture::Texture;
+use crate::renderer::cel::CelStyle;
+use crate::renderer::hair::HairStyle;
+use crate::renderer::hatching::Hatching;
+use crate::texture::{self, FilterMode, Sampler, Texture, TextureBinding, WrapMode};
 use crate::vertex::Material;
 
 #[derive(Debug)]
 pub struct FragmentData<'a> {
     pub world_pos: Vec3<f32>,
+    pub object_pos: Vec3<f32>, // 模型空间位置，物体旋转或移动时保持不变
     pub normal: Vec3<f32>,
     pub uv: Vec2<f32>,
+    // 向右/向下移动一个像素时 UV 的变化量，用于选择 mipmap 级别
+    pub uv_dx: Vec2<f32>,
+    pub uv_dy: Vec2<f32>,
     pub color: Vec3<f32>, // 顶点颜色插值结果
-    pub texture: Option<&'a Texture>,
+    pub texture: Option<TextureBinding<'a>>, // 纹理及其采样器
     pub material: &'a Material,
+    pub matcap: Option<&'a Texture>, // 所属模型的 matcap 贴图
     pub camera_pos: Vec3<f32>,
+    pub view_matrix: Mat4<f32>,
+    // 由三角形位置与 UV 求出的切线空间（世界空间）
+    pub tangent: Vec3<f32>,
+    pub bitangent: Vec3<f32>,
+    // 直接光照的可见度，1.0 表示完全受光（视差自阴影会降低它）
+    pub shadow: f32,
+    // 屏幕空间环境光遮蔽，只作用于环境光，1.0 表示无遮蔽
+    pub ambient_occlusion: f32,
+    // 像素中心的屏幕坐标（帧缓冲像素）
+    pub screen_pos: Vec2<f32>,
+}
+
+impl FragmentData<'_> {
+    // 有纹理时使用纹理颜色，否则使用顶点颜色
+    pub fn base_color(&self) -> Vec3<f32> {
+        match self.texture {
+            Some(tex) => tex.sample(self.uv, self.uv_dx, self.uv_dy),
+            None => self.color,
+        }
+    }
 }
 
 // 定义 Shader 的通用行为
 pub trait FragmentShader: Sync {
-    // 输入插值后的片元数据，输出最终的颜色 (0.0 ~ 1.0 范围的 Vec3)
+    // 输入插值后的片元数据，输出线性空间的 HDR 辐射亮度（不做截断，交给色调映射处理）
     fn shade(&self, data: FragmentData) -> Vec3<f32>;
 }
 
+// 卡通着色的三级色阶：受光量高于 0.6 为亮部，高于 0.2 为中间调，其余为暗部，返回光源强度的倍数。
+// toon 着色器与开启 toon 的头发着色器共用，保证同一模型上的明暗分界一致
+fn toon_band(light: f32) -> f32 {
+    if light > 0.6 {
+        1.1
+    } else if light > 0.2 {
+        0.8
+    } else {
+        0.5
+    }
+}
+
 //非线性漫反射：卡通风格渲染
 pub struct ToonShader {
     pub light: Light,
@@ -30,24 +73,16 @@ pub struct ToonShader {
 impl FragmentShader for ToonShader {
     fn shade(&self, data: FragmentData) -> Vec3<f32> {
         // 优先使用纹理颜色作为基础色
-        let mut base_color = data.color;
-        if let Some(tex) = data.texture {
-            base_color = tex.sample(data.uv);
-        }
+        let base_color = data.base_color();
 
         // 1. 环境光分量 (保持不变)
-        let ambient = self.light.ambient_color * self.light.ambient_strength;
+        let ambient =
+            self.light.ambient_color * self.light.ambient_strength * data.ambient_occlusion;
 
         // 2. 卡通风格的漫反射分量 (核心部分)
         let light_dir = self.light.direction.normalize();
         let diff = data.normal.dot(-light_dir).max(0.0);
-        let diffuse = if diff > 0.6 {
-            self.light.color * self.light.intensity * 1.1
-        } else if diff > 0.2 {
-            self.light.color * self.light.intensity * 0.8
-        } else {
-            self.light.color * self.light.intensity * 0.5
-        };
+        let diffuse = self.light.color * self.light.intensity * toon_band(diff * data.shadow);
 
         // 3. 高光分量 (保持不变，卡通渲染也可以有高光)
         let specular = {
@@ -62,18 +97,56 @@ impl FragmentShader for ToonShader {
             self.light.color.mul_element_wise(data.material.specular)
                 * data.material.specular_strength
                 * spec
+                * data.shadow
         };
 
         // 合并光照
         let final_lighting = ambient + diffuse + specular;
-        let mut final_color = base_color.mul_element_wise(final_lighting);
+        base_color.mul_element_wise(final_lighting)
+    }
+}
 
-        // Clamping
-        final_color.x = final_color.x.clamp(0.0, 1.0);
-        final_color.y = final_color.y.clamp(0.0, 1.0);
-        final_color.z = final_color.z.clamp(0.0, 1.0);
+// 可配置的卡通渲染：色阶或色阶贴图决定明暗，暗部使用单独的阴影色，另加菲涅尔边缘光与风格化高光
+pub struct CelShader {
+    pub light: Light,
+    pub style: Arc<CelStyle>,
+}
 
-        final_color
+impl FragmentShader for CelShader {
+    fn shade(&self, data: FragmentData) -> Vec3<f32> {
+        let base_color = data.base_color();
+        let style = &self.style;
+
+        let ambient =
+            self.light.ambient_color * self.light.ambient_strength * data.ambient_occlusion;
+
+        let light_dir = self.light.direction.normalize();
+        let view_dir = (data.camera_pos - data.world_pos).normalize();
+        let n_dot_l = data.normal.dot(-light_dir);
+        let facing = data.normal.dot(view_dir).clamp(0.0, 1.0);
+        let light_term = if style.half_lambert {
+            (n_dot_l * 0.5 + 0.5) * data.shadow
+        } else {
+            n_dot_l.max(0.0) * data.shadow
+        };
+
+        // 按色阶在阴影色与受光色之间混合
+        let weight = style.ramp(light_term, facing);
+        let lit = self.light.color * self.light.intensity;
+        let diffuse = style.shadow_color + (lit - style.shadow_color).mul_element_wise(weight);
+
+        let specular = {
+            let half_dir = (-light_dir + view_dir).normalize();
+            let spec = data.normal.dot(half_dir).max(0.0);
+            self.light.color.mul_element_wise(data.material.specular)
+                * data.material.specular_strength
+                * style.specular(spec, data.material.shininess)
+                * data.shadow
+        };
+
+        let rim = style.rim_color * style.rim(facing);
+
+        base_color.mul_element_wise(ambient + diffuse + specular) + rim
     }
 }
 
@@ -85,18 +158,16 @@ pub struct PhongShader {
 impl<'a> FragmentShader for PhongShader {
     fn shade(&self, data: FragmentData) -> Vec3<f32> {
         // 优先使用纹理颜色
-        let mut base_color = data.color;
-        if let Some(tex) = data.texture {
-            base_color = tex.sample(data.uv);
-        }
+        let base_color = data.base_color();
 
         // 环境光分量 (Ambient)
-        let ambient = self.light.ambient_color * self.light.ambient_strength;
+        let ambient =
+            self.light.ambient_color * self.light.ambient_strength * data.ambient_occlusion;
 
         // 漫反射分量 (Diffuse)
         let light_dir = self.light.direction.normalize();
         let diff = data.normal.dot(-light_dir).max(0.0);
-        let diffuse = self.light.color * self.light.intensity * diff;
+        let diffuse = self.light.color * self.light.intensity * diff * data.shadow;
 
         // 高光分量 (Specular)
         let mut specular = {
@@ -107,6 +178,7 @@ impl<'a> FragmentShader for PhongShader {
             self.light.color.mul_element_wise(data.material.specular)
                 * data.material.specular_strength
                 * spec
+                * data.shadow
         };
 
         let split_level = 6.0;
@@ -118,14 +190,44 @@ impl<'a> FragmentShader for PhongShader {
 
         // 合并光照
         let final_lighting = ambient + diffuse + specular;
-        let mut final_color = base_color.mul_element_wise(final_lighting);
+        base_color.mul_element_wise(final_lighting)
+    }
+}
 
-        // 最后进行Clamping，确保颜色值在有效范围内
-        final_color.x = final_color.x.clamp(0.0, 1.0);
-        final_color.y = final_color.y.clamp(0.0, 1.0);
-        final_color.z = final_color.z.clamp(0.0, 1.0);
+// 纯色着色：直接输出顶点颜色或纹理颜色，不受光照影响（反转外壳描边使用）
+pub struct UnlitShader;
 
-        final_color
+impl FragmentShader for UnlitShader {
+    fn shade(&self, data: FragmentData) -> Vec3<f32> {
+        data.base_color()
+    }
+}
+
+// 材质捕获（matcap）：按观察空间法线在球形光照贴图上取色，与场景光照无关，适合快速预览雕刻模型。
+// 以视线方向为基准建立坐标系再投影法线，画面边缘的物体也不会因透视而取到错误的位置
+pub struct MatcapShader;
+
+impl FragmentShader for MatcapShader {
+    fn shade(&self, data: FragmentData) -> Vec3<f32> {
+        let view_pos = (data.view_matrix * data.world_pos.extend(1.0)).truncate();
+        let normal = (data.view_matrix * data.normal.extend(0.0)).truncate().normalize();
+        let view_dir = (-view_pos).normalize();
+        let Some(matcap) = data.matcap else {
+            // 没有 matcap 贴图时按朝向相机的程度显示基础颜色
+            return data.base_color() * normal.dot(view_dir).max(0.0) * data.ambient_occlusion;
+        };
+        let x = Vec3::new(view_dir.z, 0.0, -view_dir.x).normalize();
+        let y = view_dir.cross(x);
+        // 略小于 0.5，避免取到贴图边缘圆形之外的像素
+        let uv = Vec2::new(x.dot(normal), y.dot(normal)) * 0.495 + Vec2::new(0.5, 0.5);
+        let sampler = Sampler {
+            wrap_u: WrapMode::ClampToEdge,
+            wrap_v: WrapMode::ClampToEdge,
+            filter: FilterMode::Bilinear,
+            ..Default::default()
+        };
+        let zero = Vec2::new(0.0, 0.0);
+        matcap.sample(&sampler, uv, zero, zero) * data.ambient_occlusion
     }
 }
 
@@ -139,26 +241,166 @@ impl FragmentShader for NormalDebugShader {
     }
 }
 
+// 可视化各向异性过滤的采样比：蓝色为 1x，经绿、黄到红色为 16x
+pub struct AnisotropyDebugShader;
+
+impl FragmentShader for AnisotropyDebugShader {
+    fn shade(&self, data: FragmentData) -> Vec3<f32> {
+        let ratio = match data.texture {
+            Some(tex) => tex.anisotropy(data.uv_dx, data.uv_dy),
+            None => {
+                // 没有纹理时按正方形纹理估计，比值与纹理尺寸无关
+                let (len_x, len_y) = (data.uv_dx.magnitude(), data.uv_dy.magnitude());
+                texture::anisotropy_ratio(len_x.max(len_y), len_x.min(len_y), texture::MAX_ANISOTROPY)
+            }
+        };
+        let t = ratio.log2() / (texture::MAX_ANISOTROPY as f32).log2();
+        let heat = Vec3::new(
+            (t * 2.0 - 0.5).clamp(0.0, 1.0),
+            (1.0 - (t * 2.0 - 1.0).abs()).clamp(0.0, 1.0),
+            (1.0 - t * 2.0).clamp(0.0, 1.0),
+        );
+        // 叠加一点法线明暗，便于看清形体
+        let shade = 0.6 + 0.4 * data.normal.y.abs();
+        heat * shade
+    }
+}
+
+// 头发着色：漫反射沿用包裹光照（或卡通色阶），高光为沿发丝切线的双层 Kajiya–Kay 各向异性高光
+pub struct HairShader {
+    pub light: Light,
+    pub style: Arc<HairStyle>,
+}
+
+impl FragmentShader for HairShader {
+    fn shade(&self, data: FragmentData) -> Vec3<f32> {
+        let base_color = data.base_color();
+        let style = &self.style;
+
+        let ambient =
+            self.light.ambient_color * self.light.ambient_strength * data.ambient_occlusion;
+
+        let light_dir = self.light.direction.normalize();
+        let n_dot_l = data.normal.dot(-light_dir);
+        // 头发是半透明的细丝，背光处不会完全变暗，这里用包裹光照近似；卡通模式与 toon 着色器的色阶一致
+        let diffuse = if style.toon {
+            self.light.color * self.light.intensity * toon_band(n_dot_l.max(0.0) * data.shadow)
+        } else {
+            self.light.color * self.light.intensity * (0.25 + 0.75 * n_dot_l.max(0.0) * data.shadow)
+        };
+
+        let specular = {
+            let has_uv = data.uv_dx.magnitude2() + data.uv_dy.magnitude2() > 0.0;
+            let strand =
+                style.strand_tangent(data.normal, data.tangent, data.bitangent, data.uv, has_uv);
+            let (shift, mask) =
+                style.strand_noise(data.uv, has_uv, data.world_pos, data.normal, strand);
+            let view_dir = (data.camera_pos - data.world_pos).normalize();
+            let half_dir = (-light_dir + view_dir).normalize();
+            let primary = style.lobe(&style.primary, strand, data.normal, half_dir, shift);
+            let secondary = style.lobe(&style.secondary, strand, data.normal, half_dir, shift);
+            // 高光只出现在受光面，在明暗交界处逐渐消失
+            let visibility = (n_dot_l * 4.0).clamp(0.0, 1.0) * data.shadow;
+            (style.primary.color * primary
+                + style.secondary.color.mul_element_wise(base_color) * secondary * mask)
+                .mul_element_wise(self.light.color)
+                * self.light.intensity
+                * visibility
+        };
+
+        base_color.mul_element_wise(ambient + diffuse) + specular
+    }
+}
+
+// Gooch 冷暖色调参数：背光面趋向冷色，受光面趋向暖色，表面颜色按比例混入两端
+#[derive(Debug, Clone, Copy)]
+pub struct GoochTone {
+    pub cool: Vec3<f32>,
+    pub warm: Vec3<f32>,
+    pub alpha: f32,     // 冷色中混入表面颜色的比例
+    pub beta: f32,      // 暖色中混入表面颜色的比例
+    pub highlight: f32, // 白色高光的强度，0 为不加高光
+}
+
+impl Default for GoochTone {
+    fn default() -> Self {
+        Self {
+            cool: Vec3::new(0.0, 0.0, 0.55),
+            warm: Vec3::new(0.3, 0.3, 0.0),
+            alpha: 0.25,
+            beta: 0.5,
+            highlight: 1.0,
+        }
+    }
+}
+
+// Gooch 技术插图着色：用冷暖色相的变化代替明暗表达形状，暗部不会变黑，
+// 便于与黑色的轮廓线和折痕线搭配
+pub struct GoochShader {
+    pub light: Light,
+    pub tone: GoochTone,
+}
+
+impl FragmentShader for GoochShader {
+    fn shade(&self, data: FragmentData) -> Vec3<f32> {
+        let base_color = data.base_color();
+        let tone = &self.tone;
+
+        let light_dir = self.light.direction.normalize();
+        let n_dot_l = data.normal.dot(-light_dir);
+        // 把 [-1, 1] 的光照项映射为冷暖之间的混合权重
+        let t = (1.0 + n_dot_l) * 0.5;
+        let cool = tone.cool + base_color * tone.alpha;
+        let warm = tone.warm + base_color * tone.beta;
+        let gooch = cool + (warm - cool) * t;
+
+        let specular = {
+            let view_dir = (data.camera_pos - data.world_pos).normalize();
+            let half_dir = (-light_dir + view_dir).normalize();
+            let spec = data.normal.dot(half_dir).max(0.0);
+            spec.powf(data.material.shininess) * tone.highlight * data.shadow
+        };
+
+        gooch + Vec3::new(specular, specular, specular)
+    }
+}
+
+// 水墨着色中随机墨点与亮斑的参数：随机数由种子和模型空间坐标所在的格子决定，
+// 同一场景每次渲染结果相同，墨点附着在物体表面上，物体旋转或移动时也不会在表面上游走
+#[derive(Debug, Clone, Copy)]
+pub struct InkNoise {
+    pub seed: u32,
+    pub grain_size: f32, // 一个墨点格子的模型空间边长
+}
+
+impl Default for InkNoise {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            grain_size: 0.02,
+        }
+    }
+}
+
 pub struct InkShader {
     pub light: Light,
+    pub noise: InkNoise,
 }
 
 impl FragmentShader for InkShader {
     fn shade(&self, data: FragmentData) -> Vec3<f32> {
-        let mut base_color = data.color;
-        if let Some(tex) = data.texture {
-            base_color = tex.sample(data.uv);
-        }
-        let gray = base_color.x * 0.299 + base_color.y * 0.587 + base_color.z * 0.114;
+        let base_color = data.base_color();
+        let gray = color::luminance(base_color);
         let gray_color = Vec3::new(gray, gray, gray);
 
-        let ambient = self.light.ambient_color * self.light.ambient_strength;
+        let ambient =
+            self.light.ambient_color * self.light.ambient_strength * data.ambient_occlusion;
 
         let light_dir = self.light.direction.normalize();
         let diff = data.normal.dot(-light_dir).max(0.0);
-        let diffuse = if diff > 0.8 {
+        let diffuse = if diff * data.shadow > 0.8 {
             self.light.color * self.light.intensity * 1.1
-        } else if diff > 0.3 {
+        } else if diff * data.shadow > 0.3 {
             self.light.color * self.light.intensity * 0.6
         } else {
             self.light.color * self.light.intensity * 0.05
@@ -171,6 +413,7 @@ impl FragmentShader for InkShader {
             self.light.color.mul_element_wise(data.material.specular)
                 * data.material.specular_strength
                 * spec
+                * data.shadow
         };
 
         let split_level = 4.0;
@@ -181,7 +424,8 @@ impl FragmentShader for InkShader {
         );
         let mut final_color = gray_color.mul_element_wise(ambient + diffuse + specular);
 
-        let rnumber = rand::random_range(0..=100);
+        let rnumber =
+            (noise::random_at(self.noise.seed, data.object_pos, self.noise.grain_size) * 101.0) as u32;
         match rnumber {
             0..2 => {
                 if final_color.x < 0.2 {
@@ -194,10 +438,47 @@ impl FragmentShader for InkShader {
             _ => {}
         }
 
-        final_color.x = final_color.x.clamp(0.0, 1.0);
-        final_color.y = final_color.y.clamp(0.0, 1.0);
-        final_color.z = final_color.z.clamp(0.0, 1.0);
-
         final_color
     }
 }
+
+// 素描排线：按光照后的明暗在色调图之间混合铅笔笔触，输出黑白画面
+pub struct HatchingShader {
+    pub light: Light,
+    pub hatching: Arc<Hatching>,
+}
+
+impl FragmentShader for HatchingShader {
+    fn shade(&self, data: FragmentData) -> Vec3<f32> {
+        let gray = color::luminance(data.base_color());
+
+        let ambient =
+            self.light.ambient_color * self.light.ambient_strength * data.ambient_occlusion;
+
+        let light_dir = self.light.direction.normalize();
+        let diff = data.normal.dot(-light_dir).max(0.0);
+        let diffuse = self.light.color * self.light.intensity * diff * data.shadow;
+
+        let specular = {
+            let view_dir = (data.camera_pos - data.world_pos).normalize();
+            let half_dir = (-light_dir + view_dir).normalize();
+            let spec = data.normal.dot(half_dir).max(0.0);
+            let spec = spec.powf(data.material.shininess);
+            self.light.color.mul_element_wise(data.material.specular)
+                * data.material.specular_strength
+                * spec
+                * data.shadow
+        };
+
+        // 色调按显示亮度计算，中间调才会落在中等密度的笔触上
+        let radiance = gray * color::luminance(ambient + diffuse) + color::luminance(specular);
+        let tone = color::linear_to_srgb(radiance.clamp(0.0, 1.0));
+
+        let hatching = &self.hatching;
+        let (uv, uv_dx, uv_dy) =
+            hatching.coordinates(data.screen_pos, data.uv, data.uv_dx, data.uv_dy);
+        let value = hatching.hatch(tone, uv, uv_dx, uv_dy) * hatching.paper_at(data.screen_pos);
+        let value = color::srgb_to_linear(value);
+        Vec3::new(value, value, value)
+    }
+}
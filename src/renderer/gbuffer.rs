@@ -0,0 +1,92 @@
+use cgmath::{Vector2 as Vec2, Vector3 as Vec3};
+use std::sync::Arc;
+
+use crate::renderer::fragment_shader::FragmentData;
+use crate::texture::Texture;
+use crate::vertex::Material;
+
+// 写入 G-buffer 的表面种类：材质、着色器与 matcap 贴图的组合
+#[derive(Debug, Clone)]
+pub struct Surface {
+    pub material: Material,
+    pub shader: String,
+    pub matcap: Option<Arc<Texture>>,
+}
+
+// 延迟渲染的 G-buffer：几何阶段记录每个可见像素的表面属性，光照阶段再逐像素着色一次。
+// 深度、法线、运动矢量与物体编号仍然存放在 FrameBuffer 中，世界坐标由深度重建
+pub struct GBuffer {
+    pub albedo: Vec<Vec3<f32>>,     // 纹理或顶点颜色（视差偏移之后）
+    pub shadow: Vec<f32>,           // 视差自阴影给出的直接光可见度
+    pub object_pos: Vec<Vec3<f32>>, // 模型空间位置，无法由深度重建
+    // UV 及其屏幕空间导数与切线空间，供按 UV 排线与头发的发丝走向使用
+    pub uv: Vec<Vec2<f32>>,
+    pub uv_dx: Vec<Vec2<f32>>,
+    pub uv_dy: Vec<Vec2<f32>>,
+    pub tangent: Vec<Vec3<f32>>,
+    pub bitangent: Vec<Vec3<f32>>,
+    pub surface_id: Vec<u32>,       // surfaces 中的下标
+    pub surfaces: Vec<Surface>,
+}
+
+impl GBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            albedo: vec![Vec3::new(0.0, 0.0, 0.0); width * height],
+            shadow: vec![1.0; width * height],
+            object_pos: vec![Vec3::new(0.0, 0.0, 0.0); width * height],
+            uv: vec![Vec2::new(0.0, 0.0); width * height],
+            uv_dx: vec![Vec2::new(0.0, 0.0); width * height],
+            uv_dy: vec![Vec2::new(0.0, 0.0); width * height],
+            tangent: vec![Vec3::new(0.0, 0.0, 0.0); width * height],
+            bitangent: vec![Vec3::new(0.0, 0.0, 0.0); width * height],
+            surface_id: vec![0; width * height],
+            surfaces: Vec::new(),
+        }
+    }
+
+    // 每帧开始时清空表面列表；像素数据会被几何阶段覆盖，背景像素由 object_id 判断
+    pub fn clear(&mut self) {
+        self.surfaces.clear();
+    }
+
+    // 查找或登记一种表面，返回它的编号
+    pub fn surface_id(
+        &mut self,
+        material: &Material,
+        shader: &str,
+        matcap: Option<&Arc<Texture>>,
+    ) -> u32 {
+        let same_matcap = |s: &Surface| match (&s.matcap, matcap) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        let found = self
+            .surfaces
+            .iter()
+            .position(|s| s.shader == shader && s.material == *material && same_matcap(s));
+        let index = found.unwrap_or_else(|| {
+            self.surfaces.push(Surface {
+                material: *material,
+                shader: shader.to_string(),
+                matcap: matcap.cloned(),
+            });
+            self.surfaces.len() - 1
+        });
+        index as u32
+    }
+
+    // 记录片元（视差偏移之后）的表面属性，纹理在这里采样为 albedo
+    pub fn write(&mut self, idx: usize, data: &FragmentData, surface_id: u32) {
+        self.albedo[idx] = data.base_color();
+        self.shadow[idx] = data.shadow;
+        self.object_pos[idx] = data.object_pos;
+        self.uv[idx] = data.uv;
+        self.uv_dx[idx] = data.uv_dx;
+        self.uv_dy[idx] = data.uv_dy;
+        self.tangent[idx] = data.tangent;
+        self.bitangent[idx] = data.bitangent;
+        self.surface_id[idx] = surface_id;
+    }
+}
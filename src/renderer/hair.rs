@@ -0,0 +1,130 @@
+use cgmath::{InnerSpace, Vector2 as Vec2, Vector3 as Vec3};
+
+use crate::color::luminance;
+use crate::noise::value_noise;
+use crate::renderer::cel::step;
+use crate::texture::Texture;
+
+// 发丝走向的来源
+pub enum StrandDirection {
+    U,                // 沿纹理 U 方向
+    V,                // 沿纹理 V 方向，头发贴图通常从发根到发梢沿 V 展开
+    FlowMap(Texture), // 流向图：RG 通道为切线空间中的走向，0.5 表示 0
+    World,            // 固定使用 world_direction，适合没有 UV 的模型
+}
+
+// 一层高光：shift 把切线沿法线方向偏移，使高光带沿发丝上下移动
+#[derive(Debug, Clone, Copy)]
+pub struct HairLobe {
+    pub color: Vec3<f32>,
+    pub shift: f32,
+    pub exponent: f32, // 越大高光带越窄
+    pub strength: f32,
+}
+
+// 头发着色参数：Kajiya–Kay 各向异性高光沿发丝切线分布，
+// 主高光偏向发梢、颜色接近光源色，次高光偏向发根、带头发本身的颜色并被噪声打散
+pub struct HairStyle {
+    pub direction: StrandDirection,
+    // 没有 UV 时使用的世界空间发丝方向
+    pub world_direction: Vec3<f32>,
+    pub shift_map: Option<Texture>, // 按 UV 采样的偏移贴图，亮度 0.5 为不偏移
+    pub shift_strength: f32,
+    pub noise_map: Option<Texture>, // 按 UV 采样的次高光遮罩
+    pub strand_density: f32,        // 没有贴图时程序噪声在垂直发丝方向上每单位的条纹数
+    pub seed: u32,
+    pub primary: HairLobe,
+    pub secondary: HairLobe,
+    pub toon: bool,          // 漫反射使用 toon 着色器的色阶，高光变为硬边色带
+    pub toon_threshold: f32, // 高光色带的阈值
+    pub toon_softness: f32,  // 高光色带边缘的过渡宽度，0 为硬边
+}
+
+impl HairStyle {
+    // 发丝在世界空间中的切线，已投影到与法线垂直的平面内
+    pub fn strand_tangent(
+        &self,
+        normal: Vec3<f32>,
+        tangent: Vec3<f32>,
+        bitangent: Vec3<f32>,
+        uv: Vec2<f32>,
+        has_uv: bool,
+    ) -> Vec3<f32> {
+        let strand = match &self.direction {
+            StrandDirection::U if has_uv => tangent,
+            StrandDirection::V if has_uv => bitangent,
+            StrandDirection::FlowMap(map) if has_uv => {
+                let flow = map.sample_bilinear(uv);
+                tangent * (flow.x * 2.0 - 1.0) + bitangent * (flow.y * 2.0 - 1.0)
+            }
+            _ => self.world_direction,
+        };
+        let projected = strand - normal * normal.dot(strand);
+        if projected.magnitude2() > 1e-8 {
+            return projected.normalize();
+        }
+        // 走向与法线平行（如头顶正对 world_direction 处），随便取一个与法线垂直的方向
+        let helper = if normal.x.abs() < 0.9 {
+            Vec3::unit_x()
+        } else {
+            Vec3::unit_y()
+        };
+        helper.cross(normal).normalize()
+    }
+
+    // 返回 (切线偏移量, 次高光遮罩)。有贴图时按 UV 采样；
+    // 否则用沿发丝方向拉长的值噪声，使偏移与遮罩呈现一缕一缕的条纹
+    pub fn strand_noise(
+        &self,
+        uv: Vec2<f32>,
+        has_uv: bool,
+        world_pos: Vec3<f32>,
+        normal: Vec3<f32>,
+        strand: Vec3<f32>,
+    ) -> (f32, f32) {
+        let (across, along) = match self.direction {
+            StrandDirection::U if has_uv => (uv.y, uv.x),
+            StrandDirection::V | StrandDirection::FlowMap(_) if has_uv => (uv.x, uv.y),
+            _ => (world_pos.dot(strand.cross(normal)), world_pos.dot(strand)),
+        };
+        let density = self.strand_density.max(0.0);
+        let procedural = |seed: u32| value_noise(seed, across * density, along * density * 0.05);
+        let shift = match &self.shift_map {
+            Some(map) if has_uv => luminance(map.sample_bilinear(uv)),
+            _ => procedural(self.seed),
+        };
+        let mask = match &self.noise_map {
+            Some(map) if has_uv => luminance(map.sample_bilinear(uv)),
+            _ => procedural(self.seed.wrapping_add(1)),
+        };
+        ((shift - 0.5) * self.shift_strength, mask)
+    }
+
+    // Kajiya–Kay 高光：发丝可看作细圆柱，高光强度取决于半程向量与切线夹角的正弦，
+    // dir_atten 让高光在半程向量越过切线垂面之后逐渐消失
+    pub fn lobe(
+        &self,
+        lobe: &HairLobe,
+        strand: Vec3<f32>,
+        normal: Vec3<f32>,
+        half_dir: Vec3<f32>,
+        shift: f32,
+    ) -> f32 {
+        let shifted = (strand + normal * (lobe.shift + shift)).normalize();
+        let t_dot_h = shifted.dot(half_dir);
+        let sin_th = (1.0 - t_dot_h * t_dot_h).max(0.0).sqrt();
+        let dir_atten = smoothstep(-1.0, 0.0, t_dot_h);
+        let spec = dir_atten * sin_th.powf(lobe.exponent);
+        let spec = if self.toon {
+            step(self.toon_threshold, self.toon_softness, spec)
+        } else {
+            spec
+        };
+        spec * lobe.strength
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
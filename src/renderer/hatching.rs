@@ -0,0 +1,209 @@
+use cgmath::{InnerSpace, Vector2 as Vec2, Vector3 as Vec3};
+use std::path::Path;
+
+use crate::color::luminance;
+use crate::noise::hash;
+use crate::texture::{FilterMode, Sampler, Texture, WrapMode};
+
+// 程序生成的色调图与纸纹的边长（纹素）
+const TAM_SIZE: usize = 256;
+
+// 排线纹理所在的坐标空间
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HatchSpace {
+    Screen, // 屏幕空间：笔触粗细固定，不随物体移动
+    Uv,     // 物体 UV 空间：笔触贴在表面上；模型没有 UV 时退回屏幕空间
+}
+
+// 素描排线：按亮度在一组由浅到深的色调图 (Tonal Art Map) 之间混合，
+// 暗部可以再叠加一层旋转 90° 的交叉排线，最后乘上纸纹
+pub struct Hatching {
+    pub tones: Vec<Texture>, // 由浅到深，程序生成时每一级都包含上一级的全部笔触
+    pub paper: Option<Texture>,
+    pub space: HatchSpace,
+    pub scale: f32, // 屏幕空间为一张色调图覆盖的像素数，UV 空间为 UV 方向的重复次数
+    pub cross_hatch: bool,
+    pub cross_threshold: f32, // 亮度低于该值时开始叠加交叉排线
+    pub paper_strength: f32,
+    pub paper_scale: f32, // 一张纸纹覆盖的像素数
+}
+
+impl Hatching {
+    // 返回 [0, 1] 的画面明暗，tone 为按显示亮度计算的 [0, 1] 色调
+    pub fn hatch(&self, tone: f32, uv: Vec2<f32>, uv_dx: Vec2<f32>, uv_dy: Vec2<f32>) -> f32 {
+        let mut value = self.sample_tone(tone, uv, uv_dx, uv_dy);
+        if self.cross_hatch && tone < self.cross_threshold {
+            let rotate = |v: Vec2<f32>| Vec2::new(-v.y, v.x);
+            let cross = self.sample_tone(tone, rotate(uv), rotate(uv_dx), rotate(uv_dy));
+            let weight = 1.0 - tone / self.cross_threshold.max(1e-4);
+            value *= 1.0 - (1.0 - cross) * weight;
+        }
+        value
+    }
+
+    // 纸纹明暗，1 为不变
+    pub fn paper_at(&self, screen_pos: Vec2<f32>) -> f32 {
+        match &self.paper {
+            Some(paper) if self.paper_strength > 0.0 => {
+                let inv = 1.0 / self.paper_scale.max(1.0);
+                let grain = luminance(paper.sample(
+                    &tone_sampler(),
+                    screen_pos * inv,
+                    Vec2::new(inv, 0.0),
+                    Vec2::new(0.0, inv),
+                ));
+                1.0 - (1.0 - grain) * self.paper_strength
+            }
+            _ => 1.0,
+        }
+    }
+
+    // 求排线纹理坐标及其屏幕空间导数
+    pub fn coordinates(
+        &self,
+        screen_pos: Vec2<f32>,
+        uv: Vec2<f32>,
+        uv_dx: Vec2<f32>,
+        uv_dy: Vec2<f32>,
+    ) -> (Vec2<f32>, Vec2<f32>, Vec2<f32>) {
+        let has_uv = uv_dx.magnitude2() + uv_dy.magnitude2() > 0.0;
+        match self.space {
+            HatchSpace::Uv if has_uv => (uv * self.scale, uv_dx * self.scale, uv_dy * self.scale),
+            _ => {
+                let inv = 1.0 / self.scale.max(1.0);
+                (screen_pos * inv, Vec2::new(inv, 0.0), Vec2::new(0.0, inv))
+            }
+        }
+    }
+
+    // 在相邻两级色调图之间线性混合，第 0 级之前视为白纸
+    fn sample_tone(&self, tone: f32, uv: Vec2<f32>, uv_dx: Vec2<f32>, uv_dy: Vec2<f32>) -> f32 {
+        let count = self.tones.len();
+        if count == 0 {
+            return 1.0;
+        }
+        let position = (1.0 - tone.clamp(0.0, 1.0)) * count as f32;
+        let index = (position.floor() as usize).min(count);
+        let t = position - index as f32;
+        let sampler = tone_sampler();
+        let level = |i: usize| {
+            if i == 0 {
+                1.0
+            } else {
+                luminance(self.tones[i - 1].sample(&sampler, uv, uv_dx, uv_dy))
+            }
+        };
+        let light = level(index);
+        if t <= 0.0 || index == count {
+            return light;
+        }
+        light + (level(index + 1) - light) * t
+    }
+}
+
+fn tone_sampler() -> Sampler {
+    Sampler {
+        wrap_u: WrapMode::Repeat,
+        wrap_v: WrapMode::Repeat,
+        filter: FilterMode::Trilinear,
+        ..Default::default()
+    }
+}
+
+// 读取用户提供的色调图，按由浅到深的顺序排列。
+// 色调图与程序生成的一样按显示亮度保存，不做 sRGB 解码，着色器输出时才统一转回线性空间
+pub fn load_tones(paths: &[String]) -> Result<Vec<Texture>, image::ImageError> {
+    paths
+        .iter()
+        .map(|path| Texture::from_data_file(Path::new(path)))
+        .collect()
+}
+
+// 程序生成 levels 级色调图：不断加入近似水平的铅笔笔触，平均亮度每达到一个目标就保存为一级，
+// 因此每一级都包含上一级的全部笔触，混合时笔触不会闪烁
+pub fn procedural_tones(levels: usize, seed: u32) -> Vec<Texture> {
+    let mut image = vec![1.0f32; TAM_SIZE * TAM_SIZE];
+    let mut tones = Vec::with_capacity(levels);
+    let mut stroke = 0;
+    for level in 0..levels {
+        // 最深一级的平均亮度约为 0.15
+        let target = 1.0 - 0.85 * (level + 1) as f32 / levels as f32;
+        while image.iter().sum::<f32>() / image.len() as f32 > target {
+            for _ in 0..8 {
+                draw_stroke(&mut image, seed, stroke);
+                stroke += 1;
+            }
+        }
+        let data = image.iter().map(|&v| Vec3::new(v, v, v)).collect();
+        tones.push(Texture::from_pixels(TAM_SIZE, TAM_SIZE, data));
+    }
+    tones
+}
+
+// 一笔：随机位置、长度与轻微倾斜，两端渐细；按列绘制，每个纹素只被同一笔影响一次
+fn draw_stroke(image: &mut [f32], seed: u32, index: u32) {
+    let random =
+        |k: u32| hash(seed.wrapping_mul(0x85EB_CA6B) ^ index.wrapping_mul(8).wrapping_add(k));
+    let size = TAM_SIZE as f32;
+    let (start_x, start_y) = (random(0) * size, random(1) * size);
+    let length = ((0.3 + 0.5 * random(2)) * size) as usize;
+    let slope = (random(3) - 0.5) * 0.15;
+    let darkness = 0.5 + 0.4 * random(4);
+    let half_width = 0.6 + 0.5 * random(5);
+
+    for i in 0..length {
+        let t = i as f32 / length as f32;
+        let pressure = (std::f32::consts::PI * t).sin().sqrt();
+        let x = (start_x as usize + i) % TAM_SIZE;
+        let center = start_y + slope * i as f32;
+        for dy in -2..=2 {
+            let row = center.round() + dy as f32;
+            let coverage = (half_width + 0.5 - (row - center).abs()).clamp(0.0, 1.0);
+            if coverage <= 0.0 {
+                continue;
+            }
+            let y = (row as i32).rem_euclid(TAM_SIZE as i32) as usize;
+            image[y * TAM_SIZE + x] *= 1.0 - darkness * coverage * pressure;
+        }
+    }
+}
+
+// 读取纸纹图片，与色调图一样按显示亮度保存
+pub fn load_paper(path: &str) -> Result<Texture, image::ImageError> {
+    Texture::from_data_file(Path::new(path))
+}
+
+// 程序生成的纸纹：几层可平铺的值噪声叠加，亮度在 0.8 ~ 1 之间
+pub fn procedural_paper(seed: u32) -> Texture {
+    let mut data = Vec::with_capacity(TAM_SIZE * TAM_SIZE);
+    for y in 0..TAM_SIZE {
+        for x in 0..TAM_SIZE {
+            let mut noise = 0.0;
+            let mut amplitude = 0.5;
+            for (octave, cells) in [8, 32, 64, 128].into_iter().enumerate() {
+                noise +=
+                    value_noise(x, y, cells, seed.wrapping_add(octave as u32 * 7919)) * amplitude;
+                amplitude *= 0.6;
+            }
+            let v = 0.8 + 0.2 * noise.clamp(0.0, 1.0);
+            data.push(Vec3::new(v, v, v));
+        }
+    }
+    Texture::from_pixels(TAM_SIZE, TAM_SIZE, data)
+}
+
+// cells x cells 的格点随机值做平滑插值，格点坐标取模使纹理可以平铺
+fn value_noise(x: usize, y: usize, cells: usize, seed: u32) -> f32 {
+    let cell_size = TAM_SIZE as f32 / cells as f32;
+    let (fx, fy) = (x as f32 / cell_size, y as f32 / cell_size);
+    let (x0, y0) = (fx.floor() as usize, fy.floor() as usize);
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (tx, ty) = (smooth(fx - x0 as f32), smooth(fy - y0 as f32));
+    let lattice = |gx: usize, gy: usize| {
+        let (gx, gy) = ((gx % cells) as u32, (gy % cells) as u32);
+        hash(seed ^ gx.wrapping_mul(0x27D4_EB2D) ^ gy.wrapping_mul(0x1656_67B1))
+    };
+    let top = lattice(x0, y0) + (lattice(x0 + 1, y0) - lattice(x0, y0)) * tx;
+    let bottom = lattice(x0, y0 + 1) + (lattice(x0 + 1, y0 + 1) - lattice(x0, y0 + 1)) * tx;
+    top + (bottom - top) * ty
+}
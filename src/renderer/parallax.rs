@@ -0,0 +1,188 @@
+use cgmath::{InnerSpace, Vector2 as Vec2, Vector3 as Vec3};
+use std::path::Path;
+
+use crate::renderer::fragment_shader::FragmentData;
+use crate::texture::Texture;
+
+// 自阴影的软硬程度，越大阴影越硬
+const SHADOW_SHARPNESS: f32 = 8.0;
+
+// 视差遮蔽贴图：在切线空间中对高度场进行光线步进，偏移片元的 UV
+pub struct ParallaxMap {
+    pub height_map: Texture,
+    pub steps: u32,
+    pub depth_scale: f32,
+    pub self_shadow: bool,
+}
+
+impl ParallaxMap {
+    pub fn from_file(
+        path: &Path,
+        steps: u32,
+        depth_scale: f32,
+        self_shadow: bool,
+    ) -> Result<Self, image::ImageError> {
+        Ok(Self {
+            height_map: Texture::from_data_file(path)?,
+            steps: steps.max(1),
+            depth_scale,
+            self_shadow,
+        })
+    }
+
+    // 高度图中白色为凸起，这里换算为从表面向下的深度 (0 ~ 1)
+    fn depth_at(&self, uv: Vec2<f32>) -> f32 {
+        1.0 - self.height_map.sample_bilinear(uv).x
+    }
+
+    // 对片元进行视差偏移，并在开启自阴影时写入 shadow
+    // to_light 为指向光源的方向（世界空间）
+    pub fn apply(&self, data: &mut FragmentData, to_light: Vec3<f32>) {
+        let normal = data.normal;
+        // Gram-Schmidt 正交化，得到与插值法线垂直的切线空间
+        let tangent = (data.tangent - normal * normal.dot(data.tangent)).normalize();
+        let bitangent = if normal.cross(tangent).dot(data.bitangent) < 0.0 {
+            -normal.cross(tangent)
+        } else {
+            normal.cross(tangent)
+        };
+        let to_tangent_space = |v: Vec3<f32>| Vec3::new(v.dot(tangent), v.dot(bitangent), v.dot(normal));
+
+        let view_dir = to_tangent_space((data.camera_pos - data.world_pos).normalize());
+        if view_dir.z <= 1e-4 {
+            return;
+        }
+
+        // 掠射角时增加步数，正视时减少
+        let max_layers = self.steps as f32;
+        let min_layers = (max_layers * 0.25).max(1.0);
+        let layers = max_layers + (min_layers - max_layers) * view_dir.z;
+        let layer_depth = 1.0 / layers;
+        let delta_uv = Vec2::new(view_dir.x, view_dir.y) / view_dir.z * self.depth_scale / layers;
+
+        let (final_uv, _) = self.march(data.uv, delta_uv, layer_depth);
+        data.uv = final_uv;
+
+        if self.self_shadow {
+            data.shadow = self.shadow_factor(final_uv, to_tangent_space(to_light.normalize()));
+        }
+    }
+
+    // 从 uv 出发沿 -delta_uv 逐层步进，直到层深度超过高度场深度，再在最后两层之间线性插值消除分层感。
+    // 步数最多为 steps 的两倍。返回偏移后的 UV 与实际步进次数
+    fn march(&self, uv: Vec2<f32>, delta_uv: Vec2<f32>, layer_depth: f32) -> (Vec2<f32>, u32) {
+        let mut current_uv = uv;
+        let mut current_layer_depth = 0.0;
+        let mut current_depth = self.depth_at(current_uv);
+        let mut prev_uv = current_uv;
+        let mut prev_layer_depth = 0.0;
+        let mut prev_depth = current_depth;
+        let mut iterations = 0;
+        while current_layer_depth < current_depth && iterations < self.steps * 2 {
+            prev_uv = current_uv;
+            prev_layer_depth = current_layer_depth;
+            prev_depth = current_depth;
+
+            current_uv -= delta_uv;
+            current_layer_depth += layer_depth;
+            current_depth = self.depth_at(current_uv);
+            iterations += 1;
+        }
+
+        let after = current_depth - current_layer_depth;
+        let before = prev_depth - prev_layer_depth;
+        let weight = if (after - before).abs() > 1e-6 {
+            after / (after - before)
+        } else {
+            0.0
+        };
+        (current_uv * (1.0 - weight) + prev_uv * weight, iterations)
+    }
+
+    // 从偏移后的位置朝光源方向步进，若被高度场遮挡则返回小于 1 的可见度
+    fn shadow_factor(&self, uv: Vec2<f32>, light_dir: Vec3<f32>) -> f32 {
+        if light_dir.z <= 0.0 {
+            return 1.0;
+        }
+        let start_depth = self.depth_at(uv);
+        if start_depth <= 0.0 {
+            return 1.0;
+        }
+
+        let layers = self.steps as f32;
+        let layer_depth = start_depth / layers;
+        let delta_uv = Vec2::new(light_dir.x, light_dir.y) / light_dir.z * self.depth_scale * layer_depth;
+
+        let mut current_uv = uv + delta_uv;
+        let mut current_layer_depth = start_depth - layer_depth;
+        let mut occlusion: f32 = 0.0;
+        let mut i = 1.0;
+        while current_layer_depth > 0.0 {
+            let depth = self.depth_at(current_uv);
+            if depth < current_layer_depth {
+                // 越靠近起点的遮挡越硬，远处的遮挡逐渐变软
+                let partial = (current_layer_depth - depth) * (1.0 - i / layers);
+                occlusion = occlusion.max(partial);
+            }
+            current_uv += delta_uv;
+            current_layer_depth -= layer_depth;
+            i += 1.0;
+        }
+
+        (1.0 - occlusion * SHADOW_SHARPNESS).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vertex::Material;
+    use cgmath::{Matrix4 as Mat4, SquareMatrix};
+
+    fn flat_map(height: f32, steps: u32) -> ParallaxMap {
+        ParallaxMap {
+            height_map: Texture::from_pixels(2, 2, vec![Vec3::new(height, height, height); 4]),
+            steps,
+            depth_scale: 0.1,
+            self_shadow: false,
+        }
+    }
+
+    #[test]
+    fn flat_height_map_keeps_uv() {
+        let map = flat_map(1.0, 16);
+        let material = Material::metal();
+        let uv = Vec2::new(0.3, 0.7);
+        let mut data = FragmentData {
+            world_pos: Vec3::new(0.0, 0.0, 0.0),
+            object_pos: Vec3::new(0.0, 0.0, 0.0),
+            normal: Vec3::unit_z(),
+            uv,
+            uv_dx: Vec2::new(0.0, 0.0),
+            uv_dy: Vec2::new(0.0, 0.0),
+            color: Vec3::new(1.0, 1.0, 1.0),
+            texture: None,
+            material: &material,
+            matcap: None,
+            // 斜着看，使每层的 UV 偏移不为零
+            camera_pos: Vec3::new(1.0, 0.5, 1.0),
+            view_matrix: Mat4::identity(),
+            tangent: Vec3::unit_x(),
+            bitangent: Vec3::unit_y(),
+            shadow: 1.0,
+            ambient_occlusion: 1.0,
+            screen_pos: Vec2::new(0.0, 0.0),
+        };
+        map.apply(&mut data, Vec3::unit_z());
+        assert!((data.uv - uv).magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn march_stops_at_iteration_cap() {
+        // 深度恒为 3，层深度永远追不上，只能靠步数上限结束
+        let map = flat_map(-2.0, 8);
+        let (uv, iterations) = map.march(Vec2::new(0.5, 0.5), Vec2::new(0.01, 0.0), 0.125);
+        assert_eq!(iterations, 16);
+        assert!(uv.x.is_finite() && uv.y.is_finite());
+    }
+}
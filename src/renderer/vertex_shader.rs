@@ -5,6 +5,7 @@ use cgmath::{InnerSpace, Matrix4 as Mat4, Vector3 as Vec3, Vector4 as Vec4};
 pub struct VertexShaderUniforms<'a> {
     pub model_matrix: &'a Mat4<f32>,
     pub mvp_matrix: &'a Mat4<f32>,
+    pub prev_mvp_matrix: &'a Mat4<f32>, // 上一帧的 MVP 矩阵
     pub normal_matrix: &'a Mat4<f32>,
 }
 
@@ -30,7 +31,9 @@ impl VertexShader for DefaultVertexShader {
         triangle.vertices.map(|v| {
             ClipSpaceVertex {
                 position: *uniforms.mvp_matrix * v.pos.extend(1.0),
+                prev_position: *uniforms.prev_mvp_matrix * v.pos.extend(1.0),
                 world_pos: (*uniforms.model_matrix * v.pos.extend(1.0)).truncate(),
+                object_pos: v.pos,
                 normal: (*uniforms.normal_matrix * v.normal.extend(0.0))
                     .truncate()
                     .normalize(),
@@ -39,4 +42,63 @@ impl VertexShader for DefaultVertexShader {
             }
         })
     }
-}
\ No newline at end of file
+}
+// 外扩描边宽度的单位
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlineWidth {
+    World(f32),  // 世界单位，近大远小
+    Screen(f32), // 屏幕像素，远近粗细一致
+}
+
+// 反转外壳描边的参数
+#[derive(Debug, Clone, Copy)]
+pub struct HullOutline {
+    pub width: OutlineWidth,
+    pub color: Vec3<f32>,
+    pub vertex_color_width: bool, // 为 true 时宽度再乘以顶点颜色的亮度，可用顶点色控制局部粗细
+}
+
+// 反转外壳的顶点着色：把顶点沿世界空间法线推出去，输出描边颜色
+pub struct OutlineVertexShader {
+    pub outline: HullOutline,
+    pub view_proj: Mat4<f32>,
+    // 屏幕上 1 像素在 w = 1 处对应的观察空间长度：2 / (proj.y.y * 视口高度)
+    pub pixel_scale: f32,
+}
+
+impl VertexShader for OutlineVertexShader {
+    fn shade_triangle(
+        &self,
+        triangle: &Triangle,
+        uniforms: &VertexShaderUniforms,
+    ) -> [ClipSpaceVertex; 3] {
+        triangle.vertices.map(|v| {
+            let world_pos = (*uniforms.model_matrix * v.pos.extend(1.0)).truncate();
+            let normal = (*uniforms.normal_matrix * v.normal.extend(0.0))
+                .truncate()
+                .normalize();
+            let position = *uniforms.mvp_matrix * v.pos.extend(1.0);
+            let mut width = match self.outline.width {
+                OutlineWidth::World(width) => width,
+                OutlineWidth::Screen(pixels) => pixels * position.w * self.pixel_scale,
+            };
+            if self.outline.vertex_color_width {
+                width *= crate::color::luminance(v.color);
+            }
+            let extruded = world_pos + normal * width;
+            let extruded_position = self.view_proj * extruded.extend(1.0);
+            // 外扩量随物体一起运动，上一帧的位置沿用同样的偏移
+            let offset = extruded_position - position;
+            ClipSpaceVertex {
+                position: extruded_position,
+                prev_position: *uniforms.prev_mvp_matrix * v.pos.extend(1.0) + offset,
+                world_pos: extruded,
+                object_pos: v.pos,
+                // 外壳画的是背面，法线取反后朝向相机，避免线稿描边把它当成折痕
+                normal: -normal,
+                uv: v.uv,
+                color: self.outline.color,
+            }
+        })
+    }
+}
@@ -1,16 +1,47 @@
 use cgmath::{
-    Deg, Matrix4 as Mat4, Vector2 as Vec2, Vector3 as Vec3, Zero,
+    Deg, InnerSpace, Matrix4 as Mat4, Vector2 as Vec2, Vector3 as Vec3, Zero,
 };
 use serde_json::from_reader;
-use std::{error::Error, fs::File, path::Path, time::Instant};
+use std::{error::Error, fs::File, path::Path, sync::Arc, time::Instant};
 
 use crate::{
     BLUE, FAR_PLANE, NEAR_PLANE, WINDOW_HEIGHT, WINDOW_WIDTH,
     camera::{Camera},
-    json_struct::{CameraConfig, JsonConfig, LightConfig, ModelConfig},
-    model::load_obj,
-    renderer::Renderer,
-    texture,
+    json_struct::{
+        CelConfig, DepthOfFieldConfig, DitherConfig, EdgeOutlineConfig, GlitchConfig, GradingConfig, HairConfig, HairLobeConfig, HalftoneConfig, HatchingConfig,
+        HullOutlineConfig, JsonConfig, KuwaharaConfig, ModelConfig, SubmeshConfig,
+        OutlineConfig, PixelArtConfig,
+        PostProcessConfig, SamplerConfig, SsaoConfig, ToneMappingConfig, WatercolorConfig,
+    },
+    model::{MeshGroup, load_obj},
+    postprocess::{
+        bloom::Bloom,
+        dither::{Dither, DitherMethod},
+        dof::DepthOfField,
+        glitch::Glitch,
+        motion_blur::MotionBlur,
+        outline::{EdgeLayer, EdgeOutline, OutlineSource, SobelOutline},
+        PostContext, PostProcess,
+        grading::ColorGrading,
+        halftone::Halftone,
+        kuwahara::Kuwahara,
+        lut::{Lut3D, LutInterpolation},
+        palette::Palette,
+        pixel_art::{Downsample, PaletteSource, PixelArt},
+        ssao::Ssao,
+        tonemap::{Exposure, ToneMapOperator, ToneMapping},
+        watercolor::Watercolor,
+    },
+    renderer::{
+        DrawResources, RenderPath, Renderer,
+        cel::{CelBand, CelRamp, CelStyle, SpecularShape},
+        fragment_shader::{GoochTone, InkNoise},
+        hair::{HairLobe, HairStyle, StrandDirection},
+        hatching::{self, HatchSpace, Hatching},
+        parallax::ParallaxMap,
+        vertex_shader::{HullOutline, OutlineWidth},
+    },
+    texture::{self, FilterMode, MAX_ANISOTROPY, Sampler, TextureBinding, WrapMode},
     vertex::{ColoredVertex, Material, Triangle},
 };
 
@@ -26,13 +57,722 @@ fn match_material(string: &str) -> Material {
     }
 }
 
-pub fn parse_json(
-    path: &Path,
-) -> Result<(CameraConfig, Vec<ModelConfig>, LightConfig), Box<dyn std::error::Error>> {
+fn match_filter(string: &str) -> FilterMode {
+    match string {
+        "nearest" => FilterMode::Nearest,
+        "bilinear" => FilterMode::Bilinear,
+        "trilinear" => FilterMode::Trilinear,
+        "anisotropic" => FilterMode::Anisotropic,
+        _ => {
+            println!("无此种纹理过滤方式，将默认使用最近邻过滤");
+            FilterMode::Nearest
+        }
+    }
+}
+
+fn match_wrap(string: &str) -> WrapMode {
+    match string {
+        "repeat" => WrapMode::Repeat,
+        "mirrored_repeat" => WrapMode::MirroredRepeat,
+        "clamp_to_edge" => WrapMode::ClampToEdge,
+        "clamp_to_border" => WrapMode::ClampToBorder,
+        _ => {
+            println!("无此种纹理寻址方式，将默认使用重复寻址");
+            WrapMode::Repeat
+        }
+    }
+}
+
+// 把旧版写在模型上的 filter / max_anisotropy 并入 sampler
+fn legacy_sampler_fields(model: &mut ModelConfig) {
+    if let Some(filter) = model.filter.take() {
+        println!("模型的 filter 字段已移入 sampler，请改写为 sampler.filter");
+        model.sampler.filter = filter;
+    }
+    if let Some(max_anisotropy) = model.max_anisotropy.take() {
+        println!("模型的 max_anisotropy 字段已移入 sampler，请改写为 sampler.max_anisotropy");
+        model.sampler.max_anisotropy = max_anisotropy;
+    }
+}
+
+fn build_sampler(config: &SamplerConfig) -> Sampler {
+    Sampler {
+        wrap_u: match_wrap(&config.wrap_u),
+        wrap_v: match_wrap(&config.wrap_v),
+        border_color: config.border_color.into(),
+        filter: match_filter(&config.filter),
+        max_anisotropy: config.max_anisotropy.clamp(1, MAX_ANISOTROPY),
+    }
+}
+
+fn match_tone_map(string: &str) -> ToneMapOperator {
+    match string {
+        "clamp" => ToneMapOperator::Clamp,
+        "reinhard" => ToneMapOperator::Reinhard,
+        "aces" => ToneMapOperator::Aces,
+        "agx" => ToneMapOperator::Agx,
+        _ => {
+            println!("无此种色调映射算子，将默认直接截断");
+            ToneMapOperator::Clamp
+        }
+    }
+}
+
+fn match_render_path(string: &str) -> RenderPath {
+    match string {
+        "forward" => RenderPath::Forward,
+        "deferred" => RenderPath::Deferred,
+        _ => {
+            println!("无此种渲染路径，将默认使用前向渲染");
+            RenderPath::Forward
+        }
+    }
+}
+
+fn match_specular_shape(string: &str) -> SpecularShape {
+    match string {
+        "none" => SpecularShape::None,
+        "soft" => SpecularShape::Soft,
+        "hard" => SpecularShape::Hard,
+        _ => {
+            println!("无此种高光形状，将默认使用硬边高光");
+            SpecularShape::Hard
+        }
+    }
+}
+
+fn build_cel(config: &CelConfig) -> Result<CelStyle, Box<dyn Error>> {
+    let ramp = if config.ramp_path.is_empty() {
+        let mut bands: Vec<CelBand> = config
+            .bands
+            .iter()
+            .map(|band| CelBand {
+                threshold: band.threshold,
+                color: band.color.into(),
+            })
+            .collect();
+        bands.sort_by(|a, b| a.threshold.total_cmp(&b.threshold));
+        CelRamp::Bands(bands)
+    } else {
+        CelRamp::Texture(texture::Texture::from_data_file(Path::new(&config.ramp_path))?)
+    };
+    Ok(CelStyle {
+        ramp,
+        half_lambert: config.half_lambert,
+        shadow_color: config.shadow_color.into(),
+        rim_color: config.rim_color.into(),
+        rim_strength: config.rim_strength,
+        rim_power: config.rim_power,
+        rim_threshold: config.rim_threshold,
+        rim_softness: config.rim_softness,
+        specular_shape: match_specular_shape(&config.specular_shape),
+        specular_size: config.specular_size,
+        specular_softness: config.specular_softness,
+    })
+}
+
+fn match_strand_direction(config: &HairConfig) -> Result<StrandDirection, Box<dyn Error>> {
+    Ok(match config.direction.as_str() {
+        "u" => StrandDirection::U,
+        "v" => StrandDirection::V,
+        "flow_map" if !config.flow_map_path.is_empty() => StrandDirection::FlowMap(
+            texture::Texture::from_data_file(Path::new(&config.flow_map_path))?,
+        ),
+        "flow_map" => {
+            println!("未设置 flow_map_path，将默认沿 V 方向");
+            StrandDirection::V
+        }
+        "world" => StrandDirection::World,
+        _ => {
+            println!("无此种发丝方向，将默认沿 V 方向");
+            StrandDirection::V
+        }
+    })
+}
+
+fn load_data_map(path: &str) -> Result<Option<texture::Texture>, Box<dyn Error>> {
+    if path.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(texture::Texture::from_data_file(Path::new(path))?))
+}
+
+fn build_hair(config: &HairConfig) -> Result<HairStyle, Box<dyn Error>> {
+    let lobe = |lobe: &HairLobeConfig| HairLobe {
+        color: lobe.color.into(),
+        shift: lobe.shift,
+        exponent: lobe.exponent,
+        strength: lobe.strength,
+    };
+    let world_direction: Vec3<f32> = config.world_direction.into();
+    Ok(HairStyle {
+        direction: match_strand_direction(config)?,
+        world_direction: if world_direction.magnitude2() > 0.0 {
+            world_direction.normalize()
+        } else {
+            Vec3::unit_y()
+        },
+        shift_map: load_data_map(&config.shift_map_path)?,
+        shift_strength: config.shift_strength,
+        noise_map: load_data_map(&config.noise_map_path)?,
+        strand_density: config.strand_density,
+        seed: config.seed,
+        primary: lobe(&config.primary),
+        secondary: lobe(&config.secondary),
+        toon: config.toon,
+        toon_threshold: config.toon_threshold,
+        toon_softness: config.toon_softness,
+    })
+}
+
+fn build_hull_outline(config: &HullOutlineConfig, ssaa_scale: usize) -> HullOutline {
+    let width = match config.units.as_str() {
+        "world" => OutlineWidth::World(config.width),
+        "screen" => OutlineWidth::Screen(config.width * ssaa_scale as f32),
+        _ => {
+            println!("无此种描边宽度单位，将默认使用屏幕像素");
+            OutlineWidth::Screen(config.width * ssaa_scale as f32)
+        }
+    };
+    HullOutline {
+        width,
+        color: config.color.into(),
+        vertex_color_width: config.vertex_color_width,
+    }
+}
+
+fn match_hatch_space(string: &str) -> HatchSpace {
+    match string {
+        "screen" => HatchSpace::Screen,
+        "uv" => HatchSpace::Uv,
+        _ => {
+            println!("无此种排线坐标空间，将默认使用屏幕空间");
+            HatchSpace::Screen
+        }
+    }
+}
+
+fn build_hatching(config: &HatchingConfig, ssaa_scale: usize) -> Result<Hatching, Box<dyn Error>> {
+    let tones = if config.tam_paths.is_empty() {
+        hatching::procedural_tones(config.levels, config.seed)
+    } else {
+        hatching::load_tones(&config.tam_paths)?
+    };
+    let paper = if config.paper_strength <= 0.0 {
+        None
+    } else if config.paper_path.is_empty() {
+        Some(hatching::procedural_paper(config.seed))
+    } else {
+        Some(hatching::load_paper(&config.paper_path)?)
+    };
+    let space = match_hatch_space(&config.space);
+    // 屏幕空间的尺寸按 1 倍 SSAA 填写
+    let scale = match space {
+        HatchSpace::Screen => config.scale * ssaa_scale as f32,
+        HatchSpace::Uv => config.scale,
+    };
+    Ok(Hatching {
+        tones,
+        paper,
+        space,
+        scale,
+        cross_hatch: config.cross_hatch,
+        cross_threshold: config.cross_threshold,
+        paper_strength: config.paper_strength,
+        paper_scale: config.paper_scale * ssaa_scale as f32,
+    })
+}
+
+fn build_ssao(config: &SsaoConfig, ssaa_scale: usize) -> Ssao {
+    Ssao {
+        radius: config.radius,
+        samples: config.samples,
+        bias: config.bias,
+        intensity: config.intensity,
+        blur_radius: config.blur_radius * ssaa_scale,
+    }
+}
+
+fn build_tone_mapping(config: &ToneMappingConfig) -> ToneMapping {
+    let exposure = if config.auto_exposure {
+        Exposure::Auto {
+            low: config.histogram_low,
+            high: config.histogram_high,
+            compensation: config.exposure,
+        }
+    } else {
+        Exposure::Manual(config.exposure)
+    };
+    ToneMapping {
+        operator: match_tone_map(&config.operator),
+        exposure,
+    }
+}
+
+fn match_lut_interpolation(string: &str) -> LutInterpolation {
+    match string {
+        "trilinear" => LutInterpolation::Trilinear,
+        "tetrahedral" => LutInterpolation::Tetrahedral,
+        _ => {
+            println!("无此种 LUT 插值方式，将默认使用四面体插值");
+            LutInterpolation::Tetrahedral
+        }
+    }
+}
+
+fn build_grading(config: &GradingConfig) -> Result<ColorGrading, Box<dyn Error>> {
+    let lut = if config.lut_path.is_empty() {
+        None
+    } else {
+        Some(Lut3D::from_cube_file(Path::new(&config.lut_path))?)
+    };
+    Ok(ColorGrading {
+        lift: config.lift.into(),
+        gamma: config.gamma.into(),
+        gain: config.gain.into(),
+        saturation: config.saturation,
+        lut,
+        lut_interpolation: match_lut_interpolation(&config.lut_interpolation),
+    })
+}
+
+fn build_glitch(config: &GlitchConfig, ssaa_scale: usize) -> Glitch {
+    let scale = ssaa_scale as f32;
+    Glitch {
+        seed: config.seed,
+        intensity: config.intensity,
+        rgb_shift: config.rgb_shift * scale,
+        slice_count: config.slice_count,
+        slice_shift: config.slice_shift * scale,
+        block_size: config.block_size * ssaa_scale,
+        block_chance: config.block_chance,
+        scanline: config.scanline,
+        scanline_period: config.scanline_period * scale,
+        noise: config.noise,
+        hold_frames: config.hold_frames,
+    }
+}
+
+fn build_kuwahara(config: &KuwaharaConfig, ssaa_scale: usize) -> Kuwahara {
+    Kuwahara {
+        radius: config.radius * ssaa_scale as f32,
+        sharpness: config.sharpness,
+        alpha: config.alpha,
+        tensor_sigma: config.tensor_sigma * ssaa_scale as f32,
+    }
+}
+
+fn build_watercolor(
+    config: &WatercolorConfig,
+    ssaa_scale: usize,
+) -> Result<Watercolor, Box<dyn Error>> {
+    let paper = if config.paper_path.is_empty() {
+        None
+    } else {
+        Some(texture::Texture::from_data_file(Path::new(&config.paper_path))?)
+    };
+    Ok(Watercolor {
+        seed: config.seed,
+        bleed_radius: config.bleed_radius * ssaa_scale as f32,
+        bleed_depth: config.bleed_depth,
+        edge_darkening: config.edge_darkening,
+        granulation: config.granulation,
+        turbulence: config.turbulence,
+        paper,
+        paper_scale: config.paper_scale * ssaa_scale as f32,
+    })
+}
+
+fn build_halftone(config: &HalftoneConfig, ssaa_scale: usize) -> Halftone {
+    Halftone {
+        cell_size: config.cell_size * ssaa_scale as f32,
+        angles: config.angles,
+        inks: config.inks.map(Vec3::from),
+        paper: config.paper.into(),
+    }
+}
+
+fn match_dither_method(config: &DitherConfig) -> DitherMethod {
+    match config.method.as_str() {
+        "bayer" => DitherMethod::Bayer(config.matrix_size),
+        "floyd_steinberg" => DitherMethod::FloydSteinberg,
+        _ => {
+            println!("无此种抖动方式，将默认使用有序抖动");
+            DitherMethod::Bayer(config.matrix_size)
+        }
+    }
+}
+
+// 调色板文件优先，没有时使用 json 中直接写出的颜色
+fn build_palette(path: &str, colors: &[[f32; 3]]) -> Result<Palette, Box<dyn Error>> {
+    if path.is_empty() {
+        Ok(Palette {
+            colors: colors.iter().map(|&c| c.into()).collect(),
+        })
+    } else {
+        Palette::from_file(Path::new(path))
+    }
+}
+
+fn build_dither(config: &DitherConfig, ssaa_scale: usize) -> Result<Dither, Box<dyn Error>> {
+    Ok(Dither {
+        method: match_dither_method(config),
+        palette: build_palette(&config.palette_path, &config.palette)?,
+        spread: config.spread,
+        pixel_size: config.pixel_size * ssaa_scale,
+    })
+}
+
+fn match_downsample(string: &str) -> Downsample {
+    match string {
+        "nearest" => Downsample::Nearest,
+        "average" => Downsample::Average,
+        _ => {
+            println!("无此种缩小方式，将默认使用块中心像素");
+            Downsample::Nearest
+        }
+    }
+}
+
+fn build_palette_source(config: &PixelArtConfig) -> Result<PaletteSource, Box<dyn Error>> {
+    if !config.palette_path.is_empty() || !config.palette.is_empty() {
+        return Ok(PaletteSource::Fixed(build_palette(&config.palette_path, &config.palette)?));
+    }
+    Ok(match config.quantizer.as_str() {
+        "median_cut" => PaletteSource::MedianCut(config.colors),
+        "kmeans" => PaletteSource::KMeans {
+            colors: config.colors,
+            iterations: config.iterations,
+        },
+        _ => {
+            println!("无此种调色板生成方式，将默认使用 k-means");
+            PaletteSource::KMeans {
+                colors: config.colors,
+                iterations: config.iterations,
+            }
+        }
+    })
+}
+
+fn build_pixel_art(config: &PixelArtConfig, ssaa_scale: usize) -> Result<PixelArt, Box<dyn Error>> {
+    Ok(PixelArt {
+        pixel_size: config.pixel_size * ssaa_scale,
+        downsample: match_downsample(&config.downsample),
+        palette: build_palette_source(config)?,
+        dither: config.dither,
+        outline: config.outline.then(|| config.outline_color.into()),
+        outline_depth: config.outline_depth,
+    })
+}
+
+fn build_depth_of_field(
+    config: &DepthOfFieldConfig,
+    camera: &Camera,
+    model_positions: &[Vec3<f32>],
+    ssaa_scale: usize,
+) -> DepthOfField {
+    // 对焦到指定模型时，取模型原点在视线方向上的距离
+    let focus_distance = match config.focus_model {
+        Some(index) if index < model_positions.len() => {
+            (model_positions[index] - camera.eye).dot(camera.front)
+        }
+        Some(_) => {
+            println!("对焦模型序号超出范围，将使用 focus_distance");
+            config.focus_distance
+        }
+        None => config.focus_distance,
+    };
+    // 由投影矩阵反推焦距：proj[1][1] = 1 / tan(fovy / 2) = 2f / 底片高度
+    let focal_length = camera.get_frustum().get_mat().y.y * config.sensor_height * 0.5;
+    DepthOfField {
+        focus_distance,
+        f_stop: config.f_stop,
+        focal_length,
+        sensor_height: config.sensor_height,
+        unit_scale: config.unit_scale,
+        max_coc: config.max_blur * ssaa_scale as f32,
+    }
+}
+
+fn match_outline_source(string: &str) -> OutlineSource {
+    match string {
+        "color" => OutlineSource::Color,
+        "depth" => OutlineSource::Depth,
+        _ => {
+            println!("无此种描边来源，将默认使用深度描边");
+            OutlineSource::Depth
+        }
+    }
+}
+
+fn build_outline(config: &OutlineConfig, ssaa_scale: usize) -> SobelOutline {
+    SobelOutline {
+        source: match_outline_source(&config.source),
+        threshold: config.threshold,
+        line_width: config.line_width * ssaa_scale,
+        color: config.color.into(),
+    }
+}
+
+fn build_edge_outline(config: &EdgeOutlineConfig, ssaa_scale: usize) -> EdgeOutline {
+    let layer = |enabled: bool, threshold: f32, color: [f32; 3]| {
+        enabled.then(|| EdgeLayer {
+            threshold,
+            color: color.into(),
+        })
+    };
+    EdgeOutline {
+        depth: layer(
+            config.depth_threshold > 0.0,
+            config.depth_threshold,
+            config.depth_color,
+        ),
+        normal: layer(
+            config.normal_angle > 0.0,
+            config.normal_angle,
+            config.normal_color,
+        ),
+        object_id: layer(config.object_id, 0.0, config.object_id_color),
+        line_width: config.line_width * ssaa_scale,
+    }
+}
+
+// 未配置 post_process 时，按旧的固定顺序由外层配置与着色器方法生成后处理链：
+// 景深、运动模糊、泛光、描边、色调映射、调色、故障效果
+fn legacy_post_process(config: &mut JsonConfig, shader_method: &str) -> Vec<PostProcessConfig> {
+    let mut passes = Vec::new();
+    if let Some(dof) = config.camera.depth_of_field.take() {
+        passes.push(PostProcessConfig::DepthOfField(dof));
+    }
+    if let Some(motion_blur) = config.motion_blur.take() {
+        passes.push(PostProcessConfig::MotionBlur(motion_blur));
+    }
+    // 泛光作用在 HDR 画面上，需要在描边和色调映射之前完成
+    if let Some(bloom) = config.bloom.take() {
+        passes.push(PostProcessConfig::Bloom(bloom));
+    }
+    let color_outline = OutlineConfig {
+        source: "color".to_string(),
+        threshold: 0.6,
+        ..Default::default()
+    };
+    let depth_outline = OutlineConfig {
+        source: "depth".to_string(),
+        threshold: 0.1,
+        ..Default::default()
+    };
+    match shader_method {
+        "ink" => {
+            passes.push(PostProcessConfig::Outline(color_outline));
+            passes.push(PostProcessConfig::Outline(depth_outline));
+        }
+        "toon" | "cel" => passes.push(PostProcessConfig::Outline(depth_outline)),
+        // 技术插图需要轮廓线与折痕线
+        "gooch" => passes.push(PostProcessConfig::Edges(std::mem::take(&mut config.gooch.edges))),
+        _ => {}
+    }
+    // 把 HDR 画面解析为显示颜色，之后的调色都在 [0, 1] 范围内进行
+    passes.push(PostProcessConfig::ToneMapping(std::mem::take(&mut config.tone_mapping)));
+    // 绘画风格的滤镜作用在显示颜色上
+    match shader_method {
+        "kuwahara" => passes.push(PostProcessConfig::Kuwahara(std::mem::take(&mut config.kuwahara))),
+        "watercolor" => {
+            passes.push(PostProcessConfig::Watercolor(std::mem::take(&mut config.watercolor)))
+        }
+        _ => {}
+    }
+    if let Some(grading) = config.grading.take() {
+        passes.push(PostProcessConfig::Grading(grading));
+    }
+    // 印刷与复古风格把调色后的最终颜色量化
+    match shader_method {
+        "halftone" => passes.push(PostProcessConfig::Halftone(std::mem::take(&mut config.halftone))),
+        "dither" => passes.push(PostProcessConfig::Dither(std::mem::take(&mut config.dither))),
+        "pixel_art" => {
+            passes.push(PostProcessConfig::PixelArt(std::mem::take(&mut config.pixel_art)))
+        }
+        _ => {}
+    }
+    // 故障效果模拟显示信号的损坏，放在所有调色之后
+    if shader_method == "glitch" {
+        passes.push(PostProcessConfig::Glitch(std::mem::take(&mut config.glitch)));
+    }
+    passes
+}
+
+// 由配置生成一个后处理 pass，像素单位的参数按 SSAA 倍数放大
+fn build_post_process(
+    config: &PostProcessConfig,
+    camera: &Camera,
+    model_positions: &[Vec3<f32>],
+    ssaa_scale: usize,
+) -> Result<Box<dyn PostProcess>, Box<dyn Error>> {
+    Ok(match config {
+        PostProcessConfig::DepthOfField(config) => Box::new(build_depth_of_field(
+            config,
+            camera,
+            model_positions,
+            ssaa_scale,
+        )),
+        PostProcessConfig::MotionBlur(config) => Box::new(MotionBlur {
+            shutter_angle: config.shutter_angle,
+            samples: config.samples,
+            max_blur: config.max_blur * ssaa_scale as f32,
+        }),
+        PostProcessConfig::Bloom(config) => Box::new(Bloom {
+            threshold: config.threshold,
+            knee: config.knee,
+            intensity: config.intensity,
+            radius: config.radius,
+            levels: config.levels,
+        }),
+        PostProcessConfig::Outline(config) => Box::new(build_outline(config, ssaa_scale)),
+        PostProcessConfig::Edges(config) => Box::new(build_edge_outline(config, ssaa_scale)),
+        PostProcessConfig::ToneMapping(config) => Box::new(build_tone_mapping(config)),
+        PostProcessConfig::Grading(config) => Box::new(build_grading(config)?),
+        PostProcessConfig::Glitch(config) => Box::new(build_glitch(config, ssaa_scale)),
+        PostProcessConfig::Kuwahara(config) => Box::new(build_kuwahara(config, ssaa_scale)),
+        PostProcessConfig::Watercolor(config) => Box::new(build_watercolor(config, ssaa_scale)?),
+        PostProcessConfig::Halftone(config) => Box::new(build_halftone(config, ssaa_scale)),
+        PostProcessConfig::Dither(config) => Box::new(build_dither(config, ssaa_scale)?),
+        PostProcessConfig::PixelArt(config) => Box::new(build_pixel_art(config, ssaa_scale)?),
+    })
+}
+
+// 读取好的模型及其绘制资源，SSAO 预渲染和正式渲染会各绘制一次
+struct SceneModel {
+    parts: Vec<ModelPart>,
+    position: Vec3<f32>,
+    rotation_mat: Mat4<f32>,
+    scale: f32,
+    spin: Vec3<f32>, // 绕自身原点的旋转角速度（度/秒）
+    texture: Option<texture::Texture>,
+    sampler: Sampler,
+    parallax: Option<ParallaxMap>,
+    outline: Option<HullOutline>,
+    matcap: Option<Arc<texture::Texture>>,
+}
+
+// 模型中使用同一着色器与材质的三角形
+struct ModelPart {
+    triangles: Vec<Triangle>,
+    shader: Option<String>, // None 表示使用命令行给出的着色器方法
+}
+
+impl SceneModel {
+    // time 时刻（秒）的模型矩阵
+    fn model_mat(&self, time: f32) -> Mat4<f32> {
+        let spin = self.spin * time;
+        let spin_mat = Mat4::from_angle_x(Deg(spin.x))
+            * Mat4::from_angle_y(Deg(spin.y))
+            * Mat4::from_angle_z(Deg(spin.z));
+        Mat4::from_translation(self.position) * spin_mat * self.rotation_mat
+            * Mat4::from_scale(self.scale)
+    }
+}
+
+// 按子网格配置把 OBJ 的各组三角形归入不同的部分。着色器、替换材质与 OBJ 材质名都相同的组合并为一个部分，
+// 每个部分写入各自的物体编号，因此同一模型中不同材质（如头发与皮肤）之间也能描出分界
+fn split_submeshes(groups: Vec<MeshGroup>, submeshes: &[SubmeshConfig]) -> Vec<ModelPart> {
+    let mut keys: Vec<(Option<&str>, Option<&str>, Option<String>)> = Vec::new();
+    let mut parts: Vec<ModelPart> = Vec::new();
+    let mut matched = vec![false; submeshes.len()];
+    for mut group in groups {
+        let index = submeshes.iter().position(|submesh| group.matches(&submesh.name));
+        if let Some(index) = index {
+            matched[index] = true;
+        }
+        let submesh = index.map(|index| &submeshes[index]);
+        let shader = submesh
+            .map(|submesh| submesh.shader.as_str())
+            .filter(|shader| !shader.is_empty());
+        let material = submesh
+            .map(|submesh| submesh.material.as_str())
+            .filter(|material| !material.is_empty());
+        if let Some(material) = material {
+            let material = match_material(material);
+            for triangle in &mut group.triangles {
+                triangle.material = material;
+            }
+        }
+        let key = (shader, material, group.material.take());
+        match keys.iter().position(|k| *k == key) {
+            Some(i) => parts[i].triangles.append(&mut group.triangles),
+            None => {
+                keys.push(key);
+                parts.push(ModelPart {
+                    triangles: group.triangles,
+                    shader: shader.map(str::to_string),
+                });
+            }
+        }
+    }
+    for (submesh, matched) in submeshes.iter().zip(matched) {
+        if !matched {
+            println!("模型中没有名为 {} 的物体、组或材质", submesh.name);
+        }
+    }
+    parts
+}
+
+// 绘制 time 时刻的场景，frame_time 为一帧的时长，用于求上一帧的模型矩阵
+fn draw_scene(
+    renderer: &mut Renderer,
+    scene: &mut [SceneModel],
+    floor: &mut Vec<Triangle>,
+    shader_method: &str,
+    time: f32,
+    frame_time: f32,
+) {
+    // 物体编号：各模型的各部分按顺序从 1 开始，地板排在最后，0 留给背景
+    let mut object_id = 1;
+    for model in scene.iter_mut() {
+        let model_mat = model.model_mat(time);
+        let prev_model_mat = model.model_mat(time - frame_time);
+        for part in &mut model.parts {
+            let resources = DrawResources {
+                texture: model.texture.as_ref().map(|texture| TextureBinding {
+                    texture,
+                    sampler: &model.sampler,
+                }),
+                parallax: model.parallax.as_ref(),
+                matcap: model.matcap.as_ref(),
+                object_id,
+            };
+            renderer.render_colored_triangles(
+                &mut part.triangles,
+                &model_mat,
+                &prev_model_mat,
+                resources,
+                part.shader.as_deref().unwrap_or(shader_method),
+            );
+            if let Some(outline) = &model.outline {
+                renderer.render_outline_hull(
+                    &part.triangles,
+                    &model_mat,
+                    &prev_model_mat,
+                    object_id,
+                    outline,
+                );
+            }
+            object_id += 1;
+        }
+        println!("成功渲染一模型");
+    }
+    let floor_mat = Mat4::from_translation(Vec3::new(0., -10., -30.));
+    let floor_resources = DrawResources {
+        object_id,
+        ..Default::default()
+    };
+    renderer.render_colored_triangles(floor, &floor_mat, &floor_mat, floor_resources, "phong");
+    println!("已绘制地板");
+}
+
+pub fn parse_json(path: &Path) -> Result<JsonConfig, Box<dyn std::error::Error>> {
     let file = File::open(Path::new(path))?;
     let config: JsonConfig = from_reader(file)?;
     println!("成功获取json");
-    Ok((config.camera, config.models, config.light))
+    Ok(config)
 }
 
 pub fn run_json() -> Result<(), Box<dyn Error>> {
@@ -54,7 +794,25 @@ pub fn run_json() -> Result<(), Box<dyn Error>> {
     let height = 1080 * ssaa_scale;
     let shader_method = args[2].clone();
     let path = args[1].clone();
-    let (camera_config, models_config, light_config) = parse_json(Path::new(&path)).unwrap();
+    let mut config = parse_json(Path::new(&path)).unwrap();
+    let post_process_config = match config.post_process.take() {
+        Some(passes) => passes,
+        None => legacy_post_process(&mut config, &shader_method),
+    };
+    let JsonConfig {
+        camera: camera_config,
+        models: models_config,
+        light: light_config,
+        animation: animation_config,
+        ssao: ssao_config,
+        pipeline: pipeline_config,
+        hatching: hatching_config,
+        ink: ink_config,
+        cel: cel_config,
+        gooch: gooch_config,
+        hair: hair_config,
+        ..
+    } = config;
     let c_position: Vec3<f32> = camera_config.position.into();
     let c_rotation = camera_config.angle.map(|v| Deg(v)).into();
     println!("相机角度：{:?}", c_rotation);
@@ -63,71 +821,200 @@ pub fn run_json() -> Result<(), Box<dyn Error>> {
 
     let mut renderer = Renderer::new(camera, width, height);
     renderer.light.set_light(light_config.color, light_config.direction);
+    let render_path = match_render_path(&pipeline_config.render_path);
+    renderer.set_render_path(render_path);
+    renderer.ink_noise = InkNoise {
+        seed: ink_config.seed,
+        grain_size: ink_config.grain_size,
+    };
+    renderer.gooch = GoochTone {
+        cool: gooch_config.cool.into(),
+        warm: gooch_config.warm.into(),
+        alpha: gooch_config.alpha,
+        beta: gooch_config.beta,
+        highlight: gooch_config.highlight,
+    };
+    // 着色器方法或任一模型的子网格用到时才创建对应的参数
+    let uses_shader = |name: &str| {
+        shader_method == name
+            || models_config
+                .iter()
+                .any(|model| model.submeshes.iter().any(|submesh| submesh.shader == name))
+    };
+    if uses_shader("cel") {
+        renderer.cel = Some(Arc::new(build_cel(&cel_config)?));
+    }
+    if uses_shader("hair") {
+        renderer.hair = Some(Arc::new(build_hair(&hair_config)?));
+    }
+    if uses_shader("hatching") {
+        renderer.hatching = Some(Arc::new(build_hatching(&hatching_config, ssaa_scale)?));
+    }
     renderer.framebuffer.clear(BLUE);
     
     
     println!("初始化完成");
     let start_time = Instant::now(); //启动
 
-    for model_config in models_config {
-        let mut model = load_obj(
+    let model_positions: Vec<Vec3<f32>> =
+        models_config.iter().map(|m| m.position.into()).collect();
+    let mut scene = Vec::new();
+    for mut model_config in models_config {
+        legacy_sampler_fields(&mut model_config);
+        let groups = load_obj(
             std::path::Path::new(&model_config.path),
             &match_material(&model_config.material),
         )?;
+        let parts = split_submeshes(groups, &model_config.submeshes);
 
         println!("成功读取模型");
-        let texture_owner: Option<texture::Texture> = if model_config.tex_path.is_empty() {
+        let texture: Option<texture::Texture> = if model_config.tex_path.is_empty() {
             None
         } else {
             Some(texture::Texture::from_file(std::path::Path::new(
                 &model_config.tex_path,
             ))?)
         };
+        let sampler = build_sampler(&model_config.sampler);
         println!("成功读取材质");
+        let parallax: Option<ParallaxMap> = match &model_config.parallax {
+            Some(parallax) if !parallax.height_path.is_empty() => Some(ParallaxMap::from_file(
+                std::path::Path::new(&parallax.height_path),
+                parallax.steps,
+                parallax.depth_scale,
+                parallax.self_shadow,
+            )?),
+            _ => None,
+        };
+        let matcap = if model_config.matcap_path.is_empty() {
+            if shader_method == "matcap" {
+                println!("模型未设置 matcap_path，将只显示基础颜色");
+            }
+            None
+        } else {
+            Some(Arc::new(texture::Texture::from_file(Path::new(&model_config.matcap_path))?))
+        };
         let [rx, ry, rz] = model_config.angle;
         let rotation_mat =
             Mat4::from_angle_x(Deg(rx)) * Mat4::from_angle_y(Deg(ry)) * Mat4::from_angle_z(Deg(rz));
-        let model_mat = Mat4::from_translation(model_config.position.into()) * rotation_mat
-            * Mat4::from_scale(model_config.scale);
-        println!("开始渲染");
-        renderer.render_colored_triangles(
-            &mut model,
-            &model_mat,
-            texture_owner.as_ref(),
-            &shader_method,
-        );
-        println!("成功渲染一模型");
+        scene.push(SceneModel {
+            parts,
+            position: model_config.position.into(),
+            rotation_mat,
+            scale: model_config.scale,
+            spin: model_config.spin.into(),
+            texture,
+            sampler,
+            parallax,
+            outline: model_config
+                .outline
+                .as_ref()
+                .map(|outline| build_hull_outline(outline, ssaa_scale)),
+            matcap,
+        });
     }
     let mut floor = create_floor();
-    renderer.render_colored_triangles(&mut floor, &Mat4::from_translation(Vec3::new(0., -10., -30.)), None, "phong");
-    println!("已绘制地板");
+    let post_process = post_process_config
+        .iter()
+        .map(|pass| build_post_process(pass, &renderer.camera, &model_positions, ssaa_scale))
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let rendering_elapsed_time = start_time.elapsed();  //三角形绘制计时
-    println!("三角形绘制过程耗时: {:.2?}", rendering_elapsed_time); 
+    // 不配置动画时只渲染一帧静止画面
+    let (frame_count, fps) = match &animation_config {
+        Some(animation) => {
+            std::fs::create_dir_all(&animation.output_dir)?;
+            (animation.frames.max(1), animation.fps.max(1.0))
+        }
+        None => (1, 30.0),
+    };
+    let frame_time = 1.0 / fps;
 
-    println!("开始进行描边处理");
-    let outline_start_time = Instant::now(); // 描边时间
-    if shader_method == "ink" {
-        renderer.draw_color_outline_sobel(0.6, 1*ssaa_scale);
-        renderer.draw_depth_outline_sobel(0.1, 1*ssaa_scale);
-    }
-    if shader_method == "toon" {
-        //renderer.draw_color_outline_sobel(0.6, 1*ssaa_scale);
-        renderer.draw_depth_outline_sobel(0.1, 1*ssaa_scale);
-    }
-    let outline_elapsed_time = outline_start_time.elapsed();
-    println!("描边过程耗时: {:.2?}", outline_elapsed_time); 
+    for frame in 0..frame_count {
+        let time = frame as f32 * frame_time;
+        if animation_config.is_some() {
+            println!("渲染第{}帧", frame);
+        }
+        renderer.framebuffer.clear(BLUE);
+        if let Some(gbuffer) = renderer.gbuffer.as_mut() {
+            gbuffer.clear();
+        }
+        let view_matrix = renderer.camera.get_view_mat();
+        let proj_matrix = *renderer.camera.get_frustum().get_mat();
 
-    println!("已渲染完成");
+        // 前向渲染时 SSAO 需要在着色前知道整幅画面的深度与法线，因此先只写深度渲染一遍
+        if let (RenderPath::Forward, Some(ssao_config)) = (render_path, &ssao_config) {
+            let ssao_start_time = Instant::now();
+            renderer.depth_prepass = true;
+            draw_scene(&mut renderer, &mut scene, &mut floor, &shader_method, time, frame_time);
+            renderer.depth_prepass = false;
+            build_ssao(ssao_config, ssaa_scale).apply(
+                &mut renderer.framebuffer,
+                &view_matrix,
+                &proj_matrix,
+            );
+            // 清空颜色与深度，保留计算好的环境光遮蔽
+            renderer.framebuffer.clear(BLUE);
+            println!("SSAO 耗时: {:.2?}", ssao_start_time.elapsed());
+        }
 
-    println!("开始后处理 (SSAA 及保存)...");
-    let post_processing_start_time = Instant::now(); // 后处理时间
+        println!("开始渲染");
+        let frame_start_time = Instant::now();
+        draw_scene(&mut renderer, &mut scene, &mut floor, &shader_method, time, frame_time);
+        let rendering_elapsed_time = frame_start_time.elapsed();  //三角形绘制计时
+        println!("三角形绘制过程耗时: {:.2?}", rendering_elapsed_time); 
+        if render_path == RenderPath::Deferred {
+            // 延迟渲染的几何阶段已经写好深度与法线，SSAO 直接在光照之前计算
+            if let Some(ssao_config) = &ssao_config {
+                let ssao_start_time = Instant::now();
+                build_ssao(ssao_config, ssaa_scale).apply(
+                    &mut renderer.framebuffer,
+                    &view_matrix,
+                    &proj_matrix,
+                );
+                println!("SSAO 耗时: {:.2?}", ssao_start_time.elapsed());
+            }
+            let lighting_start_time = Instant::now();
+            renderer.shade_deferred();
+            println!("光照阶段耗时: {:.2?}", lighting_start_time.elapsed());
+        }
+        // 相机目前不做动画，下一帧的“上一帧观察投影矩阵”就是当前的
+        renderer.prev_view_proj = renderer.camera.get_view_proj_mat();
+
+        println!("开始执行后处理链");
+        let post_context = PostContext {
+            proj: *renderer.camera.get_frustum().get_mat(),
+            frame,
+        };
+        for pass in &post_process {
+            let pass_start_time = Instant::now();
+            pass.apply(&mut renderer.framebuffer, &post_context);
+            println!("{}耗时: {:.2?}", pass.name(), pass_start_time.elapsed());
+        }
+
+        println!("已渲染完成");
+
+        println!("开始后处理 (SSAA 及保存)...");
+        let post_processing_start_time = Instant::now(); // 后处理时间
+
+        match &animation_config {
+            Some(animation) => {
+                let dir = Path::new(&animation.output_dir);
+                let frame_path = dir.join(format!("frame_{:04}.png", frame));
+                renderer.framebuffer.ssaa(ssaa_scale).save_as_image(&frame_path.to_string_lossy())?;
+                if animation.save_velocity {
+                    let velocity_path = dir.join(format!("velocity_{:04}.exr", frame));
+                    renderer
+                        .framebuffer
+                        .save_velocity_as_image(&velocity_path.to_string_lossy(), ssaa_scale)?;
+                }
+            }
+            None => renderer.framebuffer.ssaa(ssaa_scale).save_as_image("output1.png")?,
+        }
+
+        let post_processing_elapsed_time = post_processing_start_time.elapsed(); //
+        println!("后处理耗时: {:.2?}", post_processing_elapsed_time); //后处理时间
+    }
 
-    let _ = renderer.framebuffer.ssaa(ssaa_scale).save_as_image("output1.png")?;
-      
-    let post_processing_elapsed_time = post_processing_start_time.elapsed(); //
-    println!("后处理耗时: {:.2?}", post_processing_elapsed_time); //后处理时间
-    
     println!("全渲染流程已完成");  
     println!("渲染总时长：{:.2?}", start_time.elapsed());
     Ok(())
@@ -159,8 +1046,9 @@ pub fn create_floor() -> Vec<Triangle> {
     let half_size = size / 2.0;
     let cell_size = size / cell_count as f32;
 
-    let color1 = Vec3::new(0.5, 0.5, 0.5);
-    let color2 = Vec3::new(0.3, 0.3, 0.3);
+    // 线性空间颜色，分别对应 sRGB 0.5 与 0.3 的灰
+    let color1 = Vec3::new(0.214, 0.214, 0.214);
+    let color2 = Vec3::new(0.073, 0.073, 0.073);
 
     for z_idx in 0..cell_count {
         for x_idx in 0..cell_count {
@@ -1,24 +1,123 @@
-use cgmath::{Vector2 as Vec2, Vector3 as Vec3};
+use cgmath::{InnerSpace, Vector2 as Vec2, Vector3 as Vec3};
 use image::{ImageBuffer, Rgba};
 use std::path::Path;
 
+use crate::color::srgb_to_linear;
+
+// 纹理过滤方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterMode {
+    Nearest,     // 最近邻，只使用第 0 级（旧行为）
+    Bilinear,    // 在最接近的一级 mipmap 上做双线性插值
+    Trilinear,   // 在相邻两级 mipmap 上做双线性插值后再混合
+    Anisotropic, // 沿 UV 足迹长轴做多次三线性采样
+}
+
+// 各向异性过滤允许的最大采样次数
+pub const MAX_ANISOTROPY: u32 = 16;
+
+// 纹理坐标超出 [0, 1] 时的寻址方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WrapMode {
+    Repeat,         // 重复
+    MirroredRepeat, // 镜像重复
+    ClampToEdge,    // 钳制到边缘纹素
+    ClampToBorder,  // 超出范围时使用边框颜色
+}
+
+impl WrapMode {
+    // 把整数纹素坐标映射到 [0, size)，返回 None 表示应使用边框颜色
+    fn resolve(self, coord: i32, size: usize) -> Option<usize> {
+        let size = size as i32;
+        match self {
+            WrapMode::Repeat => Some(coord.rem_euclid(size) as usize),
+            WrapMode::MirroredRepeat => {
+                let period = coord.rem_euclid(size * 2);
+                if period < size {
+                    Some(period as usize)
+                } else {
+                    Some((size * 2 - 1 - period) as usize)
+                }
+            }
+            WrapMode::ClampToEdge => Some(coord.clamp(0, size - 1) as usize),
+            WrapMode::ClampToBorder => {
+                if (0..size).contains(&coord) {
+                    Some(coord as usize)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+// 采样器：描述如何从纹理中取色，与纹理本身分离，同一张纹理可以搭配不同的采样器
+#[derive(Debug, Clone, Copy)]
+pub struct Sampler {
+    pub wrap_u: WrapMode,
+    pub wrap_v: WrapMode,
+    pub border_color: Vec3<f32>,
+    pub filter: FilterMode,
+    pub max_anisotropy: u32, // 各向异性过滤的采样上限（1 ~ 16）
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Self {
+            wrap_u: WrapMode::Repeat,
+            wrap_v: WrapMode::Repeat,
+            border_color: Vec3::new(0.0, 0.0, 0.0),
+            filter: FilterMode::Nearest,
+            max_anisotropy: MAX_ANISOTROPY,
+        }
+    }
+}
+
+// mipmap 中的一级
+#[derive(Debug)]
+pub struct MipLevel {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<Vec3<f32>>,
+}
+
 #[derive(Debug)]
 pub struct Texture {
     pub width: usize,
     pub height: usize,
-    pub data: Vec<u32>,
+    pub levels: Vec<MipLevel>, // levels[0] 为原图，之后每级长宽减半直到 1x1
 }
 
 impl Texture {
     pub fn new(width: usize, height: usize) -> Self {
-        Self {
+        Self::from_level(MipLevel {
             width,
             height,
-            data: vec![0xFFFFFFFF; width * height],
-        }
+            data: vec![Vec3::new(1.0, 1.0, 1.0); width * height],
+        })
+    }
+
+    // 由程序生成的线性空间像素创建纹理
+    pub fn from_pixels(width: usize, height: usize, data: Vec<Vec3<f32>>) -> Self {
+        Self::from_level(MipLevel {
+            width,
+            height,
+            data,
+        })
     }
 
+    // 读取颜色纹理：图片按 sRGB 编码存储，这里解码到线性空间
     pub fn from_file(path: &Path) -> Result<Self, image::ImageError> {
+        let decode: Vec<f32> = (0..256).map(|i| srgb_to_linear(i as f32 / 255.0)).collect();
+        Self::load(path, |v| decode[v as usize])
+    }
+
+    // 读取数据纹理（高度图等），数值不做颜色空间转换
+    pub fn from_data_file(path: &Path) -> Result<Self, image::ImageError> {
+        Self::load(path, |v| v as f32 / 255.0)
+    }
+
+    fn load(path: &Path, convert: impl Fn(u8) -> f32) -> Result<Self, image::ImageError> {
         let img = image::open(path)?.to_rgba8();
         let (width, height) = img.dimensions();
         let mut data = Vec::with_capacity((width * height) as usize);
@@ -26,53 +125,285 @@ impl Texture {
         for y in 0..height {
             for x in 0..width {
                 let pixel = img.get_pixel(x, y);
-                let color = ((pixel[0] as u32) << 24)
-                    | ((pixel[1] as u32) << 16)
-                    | ((pixel[2] as u32) << 8)
-                    | (pixel[3] as u32);
-                data.push(color);
+                data.push(Vec3::new(
+                    convert(pixel[0]),
+                    convert(pixel[1]),
+                    convert(pixel[2]),
+                ));
             }
         }
-        Ok(Texture {
+        Ok(Self::from_level(MipLevel {
             width: width as usize,
             height: height as usize,
             data,
-        })
+        }))
     }
 
-    pub fn sample(&self, uv: Vec2<f32>) -> Vec3<f32> {
-        let u = uv.x.fract();
-        let v = uv.y.fract();
+    // 由原图生成完整的 mipmap 链
+    fn from_level(base: MipLevel) -> Self {
+        let width = base.width;
+        let height = base.height;
+        let mut levels = vec![base];
+        loop {
+            let prev = &levels[levels.len() - 1];
+            if prev.width == 1 && prev.height == 1 {
+                break;
+            }
+            let next = Self::downsample(prev);
+            levels.push(next);
+        }
+        Self {
+            width,
+            height,
+            levels,
+        }
+    }
 
-        let x = (u * self.width as f32) as usize;
-        let y = ((1.0 - v) * self.height as f32) as usize; // 翻转V轴，使UV(0,0)对应纹理左下角
+    // 2x2 盒式滤波，奇数尺寸时最后一行/列重复采样
+    fn downsample(src: &MipLevel) -> MipLevel {
+        let width = (src.width / 2).max(1);
+        let height = (src.height / 2).max(1);
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let x0 = (x * 2).min(src.width - 1);
+                let x1 = (x * 2 + 1).min(src.width - 1);
+                let y0 = (y * 2).min(src.height - 1);
+                let y1 = (y * 2 + 1).min(src.height - 1);
+                let sum = src.data[y0 * src.width + x0]
+                    + src.data[y0 * src.width + x1]
+                    + src.data[y1 * src.width + x0]
+                    + src.data[y1 * src.width + x1];
+                data.push(sum * 0.25);
+            }
+        }
+        MipLevel { width, height, data }
+    }
+
+    // 根据屏幕空间 UV 导数选择 mipmap 级别（可为小数，供三线性过滤使用）
+    pub fn mip_level(&self, duv_dx: Vec2<f32>, duv_dy: Vec2<f32>) -> f32 {
+        let (_, major, _) = self.footprint(duv_dx, duv_dy);
+        self.lod_for(major)
+    }
+
+    // 像素在纹理上的足迹近似为平行四边形，返回 (长轴方向的 UV 向量, 长轴纹素长度, 短轴纹素长度)
+    fn footprint(&self, duv_dx: Vec2<f32>, duv_dy: Vec2<f32>) -> (Vec2<f32>, f32, f32) {
+        let to_texels = |d: Vec2<f32>| Vec2::new(d.x * self.width as f32, d.y * self.height as f32);
+        let len_x = to_texels(duv_dx).magnitude();
+        let len_y = to_texels(duv_dy).magnitude();
+        if len_x >= len_y {
+            (duv_dx, len_x, len_y)
+        } else {
+            (duv_dy, len_y, len_x)
+        }
+    }
+
+    fn lod_for(&self, texels_per_pixel: f32) -> f32 {
+        if texels_per_pixel <= 1.0 {
+            return 0.0;
+        }
+        texels_per_pixel.log2().min((self.levels.len() - 1) as f32)
+    }
+
+    // 按采样器设置采样，duv_dx / duv_dy 为相邻像素间 UV 的变化量
+    pub fn sample(
+        &self,
+        sampler: &Sampler,
+        uv: Vec2<f32>,
+        duv_dx: Vec2<f32>,
+        duv_dy: Vec2<f32>,
+    ) -> Vec3<f32> {
+        match sampler.filter {
+            FilterMode::Nearest => self.fetch_nearest(sampler, 0, uv),
+            FilterMode::Bilinear => {
+                let level = self.mip_level(duv_dx, duv_dy).round() as usize;
+                self.fetch_bilinear(sampler, level, uv)
+            }
+            FilterMode::Trilinear => {
+                self.fetch_trilinear(sampler, self.mip_level(duv_dx, duv_dy), uv)
+            }
+            FilterMode::Anisotropic => {
+                let (major_axis, major, minor) = self.footprint(duv_dx, duv_dy);
+                let ratio = anisotropy_ratio(major, minor, sampler.max_anisotropy);
+                // 长轴被拆成 taps 段，每段的足迹近似各向同性
+                let taps = ratio.ceil() as usize;
+                let lod = self.lod_for(major / ratio);
+                let mut sum = Vec3::new(0.0, 0.0, 0.0);
+                for i in 0..taps {
+                    let offset = (i as f32 + 0.5) / taps as f32 - 0.5;
+                    sum += self.fetch_trilinear(sampler, lod, uv + major_axis * offset);
+                }
+                sum / taps as f32
+            }
+        }
+    }
+
+    // 在第 0 级上双线性采样（重复寻址），用于高度图等数据纹理
+    pub fn sample_bilinear(&self, uv: Vec2<f32>) -> Vec3<f32> {
+        self.fetch_bilinear(&Sampler::default(), 0, uv)
+    }
+
+    // 按寻址模式读取纹素，落在边框外时返回边框颜色
+    fn texel(&self, sampler: &Sampler, level: usize, x: i32, y: i32) -> Vec3<f32> {
+        let mip = &self.levels[level];
+        match (
+            sampler.wrap_u.resolve(x, mip.width),
+            sampler.wrap_v.resolve(y, mip.height),
+        ) {
+            (Some(x), Some(y)) => mip.data[y * mip.width + x],
+            _ => sampler.border_color,
+        }
+    }
+
+    fn fetch_nearest(&self, sampler: &Sampler, level: usize, uv: Vec2<f32>) -> Vec3<f32> {
+        let mip = &self.levels[level];
+        let x = (uv.x * mip.width as f32).floor() as i32;
+        let y = ((1.0 - uv.y) * mip.height as f32).floor() as i32; // 翻转V轴，使UV(0,0)对应纹理左下角
+        self.texel(sampler, level, x, y)
+    }
+
+    fn fetch_trilinear(&self, sampler: &Sampler, lod: f32, uv: Vec2<f32>) -> Vec3<f32> {
+        let lower = lod.floor() as usize;
+        let upper = (lower + 1).min(self.levels.len() - 1);
+        let t = lod - lower as f32;
+        self.fetch_bilinear(sampler, lower, uv) * (1.0 - t)
+            + self.fetch_bilinear(sampler, upper, uv) * t
+    }
 
-        // 3. 防止坐标越界（超出纹理尺寸）
-        let x = x.min(self.width - 1);
-        let y = y.min(self.height - 1);
+    fn fetch_bilinear(&self, sampler: &Sampler, level: usize, uv: Vec2<f32>) -> Vec3<f32> {
+        let mip = &self.levels[level];
+        let fx = uv.x * mip.width as f32 - 0.5;
+        let fy = (1.0 - uv.y) * mip.height as f32 - 0.5;
+        let x0 = fx.floor();
+        let y0 = fy.floor();
+        let tx = fx - x0;
+        let ty = fy - y0;
+        let (x0, y0) = (x0 as i32, y0 as i32);
 
-        // 4. 获取对应像素的颜色
-    let mut color = self.get_pixel_color(x, y);
+        let top = self.texel(sampler, level, x0, y0) * (1.0 - tx)
+            + self.texel(sampler, level, x0 + 1, y0) * tx;
+        let bottom = self.texel(sampler, level, x0, y0 + 1) * (1.0 - tx)
+            + self.texel(sampler, level, x0 + 1, y0 + 1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+}
+
+// 纹理与采样器的组合，着色器通过它来采样纹理
+#[derive(Debug, Clone, Copy)]
+pub struct TextureBinding<'a> {
+    pub texture: &'a Texture,
+    pub sampler: &'a Sampler,
+}
+
+impl TextureBinding<'_> {
+    pub fn sample(&self, uv: Vec2<f32>, duv_dx: Vec2<f32>, duv_dy: Vec2<f32>) -> Vec3<f32> {
+        self.texture.sample(self.sampler, uv, duv_dx, duv_dy)
+    }
+
+    // 实际使用的各向异性比（长短轴之比，限制在 max_anisotropy 以内）
+    pub fn anisotropy(&self, duv_dx: Vec2<f32>, duv_dy: Vec2<f32>) -> f32 {
+        let (_, major, minor) = self.texture.footprint(duv_dx, duv_dy);
+        anisotropy_ratio(major, minor, self.sampler.max_anisotropy)
+    }
+}
+
+// UV 足迹长短轴之比，限制在 [1, max_anisotropy]。
+// 足迹退化为一点（UV 不变或模型没有 UV）时不需要各向异性，按 1 处理
+pub fn anisotropy_ratio(major: f32, minor: f32, max_anisotropy: u32) -> f32 {
+    if major <= 1e-8 {
+        return 1.0;
+    }
+    if minor <= 1e-8 {
+        return max_anisotropy.max(1) as f32;
+    }
+    (major / minor).clamp(1.0, max_anisotropy.max(1) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec3<f32>, b: Vec3<f32>) -> bool {
+        (a - b).magnitude() < 1e-5
+    }
+
+    // 4x4 纹理，第 i 个纹素的值为 i
+    fn ramp_4x4() -> Texture {
+        let data = (0..16).map(|i| Vec3::new(i as f32, i as f32, i as f32)).collect();
+        Texture::from_pixels(4, 4, data)
+    }
 
-    // 颜色校正：降低红色和绿色通道，提高蓝色通道以中和黄色
-    color.x *= 0.9;   // 红色通道减弱10%
-    color.y *= 0.9;   // 绿色通道减弱10%
-    color.z *= 1.1;   // 蓝色通道增强10%
+    #[test]
+    fn mip_chain_is_box_filtered() {
+        let texture = ramp_4x4();
+        let sizes: Vec<_> = texture.levels.iter().map(|l| (l.width, l.height)).collect();
+        assert_eq!(sizes, vec![(4, 4), (2, 2), (1, 1)]);
 
-    // 确保颜色值在[0, 1]范围内
-    color.x = color.x.clamp(0.0, 1.0);
-    color.y = color.y.clamp(0.0, 1.0);
-    color.z = color.z.clamp(0.0, 1.0);
+        let level1 = &texture.levels[1];
+        let average = |a: f32, b: f32, c: f32, d: f32| {
+            let v = (a + b + c + d) / 4.0;
+            Vec3::new(v, v, v)
+        };
+        assert!(close(level1.data[0], average(0.0, 1.0, 4.0, 5.0)));
+        assert!(close(level1.data[1], average(2.0, 3.0, 6.0, 7.0)));
+        assert!(close(level1.data[2], average(8.0, 9.0, 12.0, 13.0)));
+        assert!(close(level1.data[3], average(10.0, 11.0, 14.0, 15.0)));
+        assert!(close(texture.levels[2].data[0], Vec3::new(7.5, 7.5, 7.5)));
+    }
+
+    #[test]
+    fn lod_grows_with_uv_derivatives() {
+        let texture = ramp_4x4();
+        let zero = Vec2::new(0.0, 0.0);
+        // 每像素 1、2、4 个纹素，对应第 0、1、2 级
+        let lods: Vec<f32> = [0.25, 0.5, 1.0]
+            .iter()
+            .map(|&d| texture.mip_level(Vec2::new(d, 0.0), zero))
+            .collect();
+        assert_eq!(lods, vec![0.0, 1.0, 2.0]);
+        // 放大时停在第 0 级，缩小到超出 mipmap 链时停在最后一级
+        assert_eq!(texture.mip_level(Vec2::new(0.01, 0.0), zero), 0.0);
+        assert_eq!(texture.mip_level(Vec2::new(0.0, 10.0), zero), 2.0);
+        assert!(texture.mip_level(Vec2::new(0.3, 0.0), zero) < texture.mip_level(Vec2::new(0.4, 0.0), zero));
+    }
 
-    color
+    #[test]
+    fn anisotropy_ratio_follows_the_footprint() {
+        let texture = ramp_4x4();
+        let sampler = Sampler {
+            filter: FilterMode::Anisotropic,
+            ..Default::default()
+        };
+        let binding = TextureBinding {
+            texture: &texture,
+            sampler: &sampler,
+        };
+        let zero = Vec2::new(0.0, 0.0);
+        assert_eq!(binding.anisotropy(zero, zero), 1.0);
+        assert_eq!(binding.anisotropy(Vec2::new(0.5, 0.0), Vec2::new(0.0, 0.5)), 1.0);
+
+        let stretched = binding.anisotropy(Vec2::new(1.0, 0.0), Vec2::new(0.0, 0.25));
+        assert!((stretched - 4.0).abs() < 1e-5);
+        assert_eq!(binding.anisotropy(Vec2::new(1.0, 0.0), Vec2::new(0.0, 0.001)), MAX_ANISOTROPY as f32);
+        assert_eq!(binding.anisotropy(Vec2::new(1.0, 0.0), zero), MAX_ANISOTROPY as f32);
+        assert_eq!(anisotropy_ratio(8.0, 1.0, 4), 4.0);
     }
 
-    fn get_pixel_color(&self, x: usize, y: usize) -> Vec3<f32> {
-        let color = self.data[y * self.width + x];
-        Vec3::new(
-            ((color >> 24) & 0xFF) as f32 / 255.0,
-            ((color >> 16) & 0xFF) as f32 / 255.0,
-            ((color >> 8) & 0xFF) as f32 / 255.0,
-        )
+    #[test]
+    fn wrap_modes_resolve_out_of_range_coordinates() {
+        // 与 fetch_nearest 一样把 UV 换算为 4 纹素宽纹理上的整数坐标
+        let resolve = |mode: WrapMode, u: f32| mode.resolve((u * 4.0).floor() as i32, 4);
+        let coords = [-0.25, 1.25, 1.0];
+        let expect = |mode: WrapMode, texels: [usize; 3]| {
+            for (u, texel) in coords.iter().zip(texels) {
+                assert_eq!(resolve(mode, *u), Some(texel), "{mode:?} at u = {u}");
+            }
+        };
+        expect(WrapMode::Repeat, [3, 1, 0]);
+        expect(WrapMode::MirroredRepeat, [0, 2, 3]);
+        expect(WrapMode::ClampToEdge, [0, 3, 3]);
+        for u in coords {
+            assert_eq!(resolve(WrapMode::ClampToBorder, u), None, "ClampToBorder at u = {u}");
+        }
     }
 }
@@ -1,7 +1,7 @@
 use cgmath::{InnerSpace, Matrix, Matrix4 as Mat4, SquareMatrix, Vector2 as Vec2, Vector3 as Vec3, Vector4 as Vec4, Zero};
 use crate::renderer::Renderer;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Material {
     pub ambient: Vec3<f32>,    // 环境光反射率（通常与漫反射相同）
     pub diffuse: Vec3<f32>,    // 漫反射率（影响物体基础颜色）
@@ -49,7 +49,9 @@ impl Material {
 #[derive(Debug, Copy, Clone)]
 pub struct ClipSpaceVertex { // 裁剪空间的三角形顶点
     pub position: Vec4<f32>, 
+    pub prev_position: Vec4<f32>, // 上一帧的裁剪空间位置，用于求运动矢量
     pub world_pos: Vec3<f32>,
+    pub object_pos: Vec3<f32>, // 模型空间位置，随物体一起运动
     pub normal: Vec3<f32>,
     pub uv: Vec2<f32>,
     pub color: Vec3<f32>,
@@ -87,7 +89,9 @@ impl ColoredVertex {
 #[derive(Debug, Clone, Copy)]
 pub struct RasterPoint {
     pub pos: Vec2<f32>,
+    pub prev_pos: Vec2<f32>, // 上一帧的屏幕坐标
     pub world_pos: Vec3<f32>,
+    pub object_pos: Vec3<f32>,
     pub color: Vec3<f32>,
     pub normal: Vec3<f32>,
     pub z: f32,